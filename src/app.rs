@@ -0,0 +1,978 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use glam::Vec2;
+use web_time::Instant;
+use winit::{
+    application::ApplicationHandler,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceEvent, DeviceId, ElementState, InnerSizeWriter, KeyEvent, MouseButton,
+        MouseScrollDelta, WindowEvent,
+    },
+    event_loop::{ActiveEventLoop, ControlFlow, DeviceEvents, EventLoopProxy},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+    window::{CursorGrabMode, Fullscreen, Window, WindowAttributes, WindowId},
+};
+
+#[cfg(target_os = "linux")]
+use crate::gamepad::Gamepads;
+use crate::{
+    config::Config, gfx::Gfx, offscreen::Effect, placement::WindowPlacement, timing::Benchmark,
+    Args,
+};
+
+/// without `--title`
+const TITLE: &str = "triangle";
+/// how many pixels of trackpad scrolling zoom as much as one line of a mouse wheel
+const PIXELS_PER_LINE: f64 = 40.0;
+/// how many pixels of mouse movement a fully pushed right stick turns the camera by per second
+const STICK_LOOK_SPEED: f32 = 400.0;
+
+/// keys that are currently held down
+#[derive(Default)]
+struct InputState {
+    pressed: HashSet<KeyCode>,
+    modifiers: ModifiersState,
+}
+
+impl InputState {
+    fn key(&mut self, key: KeyCode, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.pressed.insert(key),
+            ElementState::Released => self.pressed.remove(&key),
+        };
+    }
+
+    /// wasd as x to the right and y forward, normalized so diagonals aren't faster
+    fn movement(&self) -> Vec2 {
+        let axis = |positive, negative| {
+            self.pressed.contains(&positive) as i32 as f32
+                - self.pressed.contains(&negative) as i32 as f32
+        };
+        Vec2::new(
+            axis(KeyCode::KeyD, KeyCode::KeyA),
+            axis(KeyCode::KeyW, KeyCode::KeyS),
+        )
+        .normalize_or_zero()
+    }
+}
+
+/// counts frames to report the average framerate once per second
+struct FpsCounter {
+    frames: u32,
+    since: Instant,
+}
+
+impl FpsCounter {
+    fn new() -> Self {
+        Self {
+            frames: 0,
+            since: Instant::now(),
+        }
+    }
+
+    /// call once per presented frame, returns the framerate whenever a second is over
+    fn frame(&mut self) -> Option<f32> {
+        self.frames += 1;
+        let elapsed = self.since.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return None;
+        }
+
+        let fps = self.frames as f32 / elapsed.as_secs_f32();
+        *self = Self::new();
+        Some(fps)
+    }
+}
+
+/// sent back to the event loop once the async part of `App::create_gfx` is done
+pub struct GfxReady(WindowId, anyhow::Result<Gfx<'static>>);
+
+/// a window and everything rendered into it
+struct WindowState {
+    window: Arc<Window>,
+    title: String,
+    /// `None` until the window's `GfxReady` arrived
+    gfx: Option<Gfx<'static>>,
+    /// the order the windows were opened in, each one starts on a different clear color
+    index: u32,
+    size: PhysicalSize<u32>,
+    /// physical pixels per logical pixel, changes when the window moves between monitors
+    scale_factor: f64,
+    last_frame: Option<Instant>,
+    /// last known cursor position, to turn `CursorMoved` into relative movement
+    cursor: Option<PhysicalPosition<f64>>,
+    /// the left button is held, cursor movement orbits the camera
+    dragging: bool,
+    /// the cursor moved since the left button was pressed, so releasing it isn't a click
+    dragged: bool,
+    /// the cursor is grabbed and hidden, raw mouse motion turns the camera
+    mouse_look: bool,
+    input: InputState,
+    /// the controllers' left and right stick while the window has the focus, see
+    /// `Gamepads::sticks`
+    sticks: [Vec2; 2],
+    /// the window has a size of 0x0, there's nothing to render to until it's restored
+    minimized: bool,
+    /// rendering pauses while another window has the focus, unless `pause_unfocused` is off
+    focused: bool,
+    pause_unfocused: bool,
+    fps: FpsCounter,
+    /// the least time between two frames, `None` renders as fast as the present mode allows
+    frame_interval: Option<Duration>,
+    /// when the next frame of a capped window is due, `App::about_to_wait` requests it then
+    next_frame: Option<Instant>,
+    /// when the frame `App::about_to_wait` requested was due, the redraw schedules the next
+    /// one from it
+    requested_due: Option<Instant>,
+}
+
+impl WindowState {
+    fn new(
+        window: Arc<Window>,
+        title: String,
+        index: u32,
+        pause_unfocused: bool,
+        frame_interval: Option<Duration>,
+    ) -> Self {
+        Self {
+            size: window.inner_size(),
+            scale_factor: window.scale_factor(),
+            window,
+            title,
+            gfx: None,
+            index,
+            last_frame: None,
+            cursor: None,
+            dragging: false,
+            dragged: false,
+            mouse_look: false,
+            input: InputState::default(),
+            sticks: [Vec2::ZERO; 2],
+            minimized: false,
+            // not every platform reports the initial focus
+            focused: true,
+            pause_unfocused,
+            fps: FpsCounter::new(),
+            frame_interval,
+            next_frame: None,
+            requested_due: None,
+        }
+    }
+
+    /// renders and presents the next frame, returns how long that took or `None` if nothing
+    /// was presented
+    fn redraw(&mut self) -> anyhow::Result<Option<Duration>> {
+        // redraws stop being requested, restoring or focusing the window starts them again
+        if self.minimized || self.paused() {
+            return Ok(None);
+        }
+        let Some(gfx) = self.gfx.as_mut() else {
+            return Ok(None);
+        };
+
+        // the first frame doesn't get the initialization time as a huge spike
+        let now = Instant::now();
+        let dt = self
+            .last_frame
+            .map_or(0.0, |last_frame| (now - last_frame).as_secs_f32());
+        self.last_frame = Some(now);
+
+        gfx.poll_shader_watcher();
+        let [movement, look] = self.sticks;
+        gfx.fly((self.input.movement() + movement).clamp_length_max(1.0), dt);
+        // unlike the mouse a stick is held, it turns the camera at a speed instead of by a distance
+        if look != Vec2::ZERO {
+            let look = look * STICK_LOOK_SPEED * dt;
+            gfx.look(look.x, -look.y);
+        }
+        gfx.update(dt);
+
+        let rendered = match gfx.render() {
+            Ok(()) => true,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                // happens e.g. when alt-tabbing, the surface just needs a fresh config
+                gfx.reconfigure();
+                false
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                log::warn!("timed out acquiring the next frame, skipping it");
+                false
+            }
+            Err(err @ wgpu::SurfaceError::OutOfMemory) => {
+                Err(anyhow::anyhow!("failed to acquire the next frame: {err}"))?
+            }
+        };
+        // everything from the uniform upload to the present, the event loop isn't included
+        let frame_time = rendered.then(|| now.elapsed());
+
+        if let Some(fps) = self.fps.frame() {
+            self.window
+                .set_title(&format!("{} - {fps:.0} FPS", self.title));
+            gfx.set_fps(fps);
+        }
+        match self.frame_interval {
+            // waiting instead of sleeping here keeps the window responsive in between. a frame
+            // that's on time schedules the next one from when it was due, so the rate doesn't
+            // drift below the cap, a late one doesn't try to catch up
+            Some(interval) => {
+                let due = self
+                    .requested_due
+                    .take()
+                    .filter(|due| now < *due + interval)
+                    .unwrap_or(now);
+                self.next_frame = Some(due + interval);
+            }
+            // keep animating, with a vsync present mode `present` paces this loop
+            None => self.window.request_redraw(),
+        }
+        Ok(frame_time)
+    }
+
+    /// requests the capped frame if it's due at `now`, otherwise returns when it will be
+    fn request_due_frame(&mut self, now: Instant) -> Option<Instant> {
+        let due = self.next_frame?;
+        if due > now {
+            return Some(due);
+        }
+        // taken so a window that's paused now isn't asked over and over
+        self.requested_due = self.next_frame.take();
+        self.window.request_redraw();
+        None
+    }
+
+    /// everything that only concerns this window, `App` handles redraws and closing
+    fn window_event(&mut self, event: WindowEvent) {
+        // what the debug panel uses neither orbits the camera nor clicks through it
+        if self.gfx.as_mut().is_some_and(|gfx| gfx.panel_event(&event)) {
+            return;
+        }
+        match event {
+            WindowEvent::Resized(new_size) => {
+                self.resize(new_size);
+            }
+
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                mut inner_size_writer,
+            } => {
+                self.rescale(scale_factor, &mut inner_size_writer);
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::F11),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.toggle_fullscreen();
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::F1),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.toggle_panel();
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyV),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.toggle_vsync();
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::KeyG),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.set_mouse_look(!self.mouse_look);
+            }
+
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input.modifiers = modifiers.state();
+            }
+
+            // before the movement keys, s doesn't move the camera while saving
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key @ (KeyCode::KeyS | KeyCode::KeyO)),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.input.modifiers.control_key() => {
+                if let Some(gfx) = self.gfx.as_mut() {
+                    match key {
+                        KeyCode::KeyS => gfx.save_spawned(),
+                        _ => gfx.load_spawned(),
+                    }
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Backspace),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                // held down, this keeps removing them
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.undo_spawn();
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::F12),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.request_screenshot();
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key:
+                            PhysicalKey::Code(
+                                key @ (KeyCode::Digit1
+                                | KeyCode::Digit2
+                                | KeyCode::Digit3
+                                | KeyCode::Digit4),
+                            ),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                let effect = match key {
+                    KeyCode::Digit1 => Effect::Passthrough,
+                    KeyCode::Digit2 => Effect::Grayscale,
+                    KeyCode::Digit3 => Effect::Invert,
+                    _ => Effect::Debug,
+                };
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.set_effect(effect);
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key @ (KeyCode::Minus | KeyCode::Equal)),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                // held down, these keep shrinking or growing the scissor rect
+                let step = match key {
+                    KeyCode::Minus => -0.1,
+                    _ => 0.1,
+                };
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.resize_scissor(step);
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key:
+                            PhysicalKey::Code(
+                                key @ (KeyCode::BracketLeft
+                                | KeyCode::BracketRight
+                                | KeyCode::Comma
+                                | KeyCode::Period),
+                            ),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                // [ and ] change the gamma, , and . the exposure, repeating while held
+                if let Some(gfx) = self.gfx.as_mut() {
+                    match key {
+                        KeyCode::BracketLeft => gfx.adjust_gamma(-0.1),
+                        KeyCode::BracketRight => gfx.adjust_gamma(0.1),
+                        KeyCode::Comma => gfx.adjust_exposure(-0.1),
+                        _ => gfx.adjust_exposure(0.1),
+                    }
+                }
+            }
+
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key),
+                        state,
+                        ..
+                    },
+                ..
+            } => {
+                self.input.key(key, state);
+            }
+
+            WindowEvent::Focused(focused) => {
+                self.focus(focused);
+            }
+
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.left_mouse(state);
+            }
+
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } => {
+                if let (Some(gfx), Some(cursor)) = (self.gfx.as_mut(), self.cursor) {
+                    gfx.request_pick(cursor);
+                }
+            }
+
+            WindowEvent::MouseWheel { delta, .. } => {
+                // positive is away from the user, which zooms in
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines,
+                    MouseScrollDelta::PixelDelta(position) => (position.y / PIXELS_PER_LINE) as f32,
+                };
+                if let Some(gfx) = self.gfx.as_mut() {
+                    gfx.zoom(lines);
+                }
+            }
+
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_moved(position);
+            }
+
+            // the shader keeps the last position instead of jumping back to the corner
+            WindowEvent::CursorLeft { .. } => {}
+
+            _ => {}
+        }
+    }
+
+    /// where the window is now, `None` while it's fullscreen or minimized or if the platform
+    /// doesn't tell
+    fn placement(&self) -> Option<WindowPlacement> {
+        if self.minimized || self.window.fullscreen().is_some() {
+            return None;
+        }
+        Some(WindowPlacement {
+            position: self.window.outer_position().ok()?,
+            inner_size: self.size,
+        })
+    }
+
+    fn paused(&self) -> bool {
+        self.pause_unfocused && !self.focused
+    }
+
+    fn focus(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            // the release of anything held while switching away never arrives
+            self.input = InputState::default();
+            // the cursor shouldn't stay hidden over whatever has the focus now
+            self.set_mouse_look(false);
+        }
+        if self.paused() {
+            // the time spent in the background shouldn't show up as one huge frame or a low
+            // framerate
+            self.last_frame = None;
+            self.fps = FpsCounter::new();
+        } else {
+            self.window.request_redraw();
+        }
+    }
+
+    fn left_mouse(&mut self, state: ElementState) {
+        match state {
+            ElementState::Pressed => {
+                self.dragging = true;
+                self.dragged = false;
+            }
+            ElementState::Released => {
+                self.dragging = false;
+                if !self.dragged {
+                    if let Some(gfx) = self.gfx.as_mut() {
+                        match self.cursor {
+                            Some(cursor) if gfx.spawning() => gfx.spawn(cursor),
+                            _ => gfx.next_clear_color(),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn cursor_moved(&mut self, position: PhysicalPosition<f64>) {
+        let last = self.cursor.replace(position);
+        let Some(gfx) = self.gfx.as_mut() else {
+            return;
+        };
+        gfx.set_cursor(position);
+
+        if let (true, Some(last)) = (self.dragging, last) {
+            let (dx, dy) = (position.x - last.x, position.y - last.y);
+            if dx != 0.0 || dy != 0.0 {
+                gfx.orbit(dx as f32, dy as f32);
+                self.dragged = true;
+            }
+        }
+    }
+
+    /// grabs and hides the cursor for mouse look, or gives it back
+    fn set_mouse_look(&mut self, enabled: bool) {
+        if enabled == self.mouse_look {
+            return;
+        }
+        if enabled {
+            // locking keeps the cursor where it is, which not every platform supports. confining
+            // it to the window is the next best thing, the raw motion doesn't stop at the edges
+            let grabbed = self
+                .window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined));
+            if let Err(err) = grabbed {
+                log::warn!("failed to grab the cursor for mouse look: {err}");
+                return;
+            }
+        } else if let Err(err) = self.window.set_cursor_grab(CursorGrabMode::None) {
+            log::warn!("failed to release the cursor: {err}");
+        }
+        self.window.set_cursor_visible(!enabled);
+        self.mouse_look = enabled;
+    }
+
+    /// raw, unaccelerated mouse motion, it keeps coming while the cursor is locked in place
+    fn mouse_motion(&mut self, (dx, dy): (f64, f64)) {
+        if !self.mouse_look {
+            return;
+        }
+        if let Some(gfx) = self.gfx.as_mut() {
+            gfx.look(dx as f32, dy as f32);
+        }
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        match self.window.fullscreen() {
+            Some(_) => self.window.set_fullscreen(None),
+            None => self
+                .window
+                .set_fullscreen(Some(Fullscreen::Borderless(None))),
+        }
+
+        // the drawable size changes with the transition, don't wait for the Resized event
+        self.resize(self.window.inner_size());
+    }
+
+    /// keeps the logical size, so the window covers as much of the new monitor as of the old
+    /// one and what's drawn stays sharp at the new scale
+    fn rescale(&mut self, scale_factor: f64, inner_size_writer: &mut InnerSizeWriter) {
+        let new_size = self
+            .size
+            .to_logical::<f64>(self.scale_factor)
+            .to_physical(scale_factor);
+        self.scale_factor = scale_factor;
+        if let Err(err) = inner_size_writer.request_inner_size(new_size) {
+            log::warn!("failed to resize the window to the new scale factor: {err}");
+        }
+        if let Some(gfx) = self.gfx.as_mut() {
+            gfx.set_scale_factor(scale_factor);
+        }
+        // the surface, depth and msaa textures are recreated at the new physical size. not
+        // every platform sends a Resized afterwards
+        self.resize(new_size);
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        self.size = new_size;
+        self.minimized = new_size.width == 0 || new_size.height == 0;
+        if self.minimized {
+            // the time spent minimized shouldn't show up as one huge frame or a low framerate
+            self.last_frame = None;
+            self.fps = FpsCounter::new();
+            return;
+        }
+        if let Some(gfx) = self.gfx.as_mut() {
+            gfx.resize(self.size);
+        }
+        // maximizing/restoring doesn't always come with a redraw on its own
+        self.window.request_redraw();
+    }
+}
+
+/// owns the windows, driven directly by the event loop
+pub struct App {
+    args: Args,
+    config: Config,
+    proxy: EventLoopProxy<GfxReady>,
+    /// each with its own surface and device, the app exits once the last one is closed
+    windows: HashMap<WindowId, WindowState>,
+    /// set by `--bench`, the app exits once it's done
+    bench: Option<Benchmark>,
+    /// when the first frame was presented, `--run-for` counts from there so the setup time
+    /// isn't part of it
+    first_frame: Option<Instant>,
+    /// whatever made the app exit early, handed back to `main` by `finish`
+    error: Option<anyhow::Error>,
+    #[cfg(target_os = "linux")]
+    gamepads: Gamepads,
+}
+
+impl App {
+    pub fn new(args: Args, config: Config, proxy: EventLoopProxy<GfxReady>) -> Self {
+        let bench = args
+            .bench
+            .map(|frames| Benchmark::new(frames, args.bench_warmup));
+        Self {
+            args,
+            config,
+            proxy,
+            windows: HashMap::new(),
+            bench,
+            first_frame: None,
+            error: None,
+            #[cfg(target_os = "linux")]
+            gamepads: Gamepads::new(),
+        }
+    }
+
+    /// to be called once the event loop has exited, the web's never does
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn finish(self) -> anyhow::Result<()> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn init(&mut self, event_loop: &ActiveEventLoop) -> anyhow::Result<()> {
+        // for mouse look, only the focused window grabs the cursor
+        event_loop.listen_device_events(DeviceEvents::WhenFocused);
+        for index in 0..self.args.windows {
+            self.open_window(event_loop, index)?;
+        }
+        Ok(())
+    }
+
+    /// creates the window right away, its `Gfx` arrives later as a `GfxReady`
+    fn open_window(&mut self, event_loop: &ActiveEventLoop, index: u32) -> anyhow::Result<()> {
+        let title = self.args.title.as_deref().unwrap_or(TITLE);
+        let title = match self.args.windows {
+            1 => title.to_owned(),
+            _ => format!("{title} {}", index + 1),
+        };
+        // only the first window reopens where it was, there's no file on the web
+        let placement = (index == 0 && !cfg!(target_arch = "wasm32"))
+            .then(WindowPlacement::load)
+            .flatten();
+        let size = self.args.window_size(
+            &self.config,
+            placement.map(|placement| placement.inner_size),
+        );
+        let mut attributes = WindowAttributes::default()
+            .with_title(&title)
+            .with_inner_size(size);
+        if let Some(placement) = placement {
+            if placement.is_on_any(event_loop.available_monitors()) {
+                attributes = attributes.with_position(placement.position);
+            } else if let Some(monitor) = event_loop
+                .primary_monitor()
+                .or_else(|| event_loop.available_monitors().next())
+            {
+                log::info!("the window was on a monitor that's gone, centering it");
+                attributes = attributes.with_position(WindowPlacement::centered(&monitor, size));
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let attributes = {
+            use winit::platform::web::WindowAttributesExtWebSys;
+            // the canvas isn't part of the page otherwise
+            attributes.with_append(true)
+        };
+        let window = Arc::new(event_loop.create_window(attributes)?);
+        let id = window.id();
+        // a benchmark has to keep going no matter what
+        let pause_unfocused = !self.args.keep_rendering_unfocused && self.args.bench.is_none();
+        self.windows.insert(
+            id,
+            WindowState::new(
+                window.clone(),
+                title,
+                index,
+                pause_unfocused,
+                self.args.frame_interval(),
+            ),
+        );
+
+        self.create_gfx(id, window);
+        Ok(())
+    }
+
+    /// sets up the device and surface for a window, they arrive as a `GfxReady`
+    fn create_gfx(&self, id: WindowId, window: Arc<Window>) {
+        let args = self.args.clone();
+        let config = self.config.clone();
+        let proxy = self.proxy.clone();
+        let setup = async move {
+            let gfx = args.gfx_builder(&config).build(window).await;
+            // only fails if the event loop is gone already
+            let _ = proxy.send_event(GfxReady(id, gfx));
+        };
+        // the browser can't block, the adapter and device requests resolve on its event loop.
+        // natively they resolve right away and there's nothing to wait for
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(setup);
+        #[cfg(not(target_arch = "wasm32"))]
+        pollster::block_on(setup);
+    }
+
+    /// finishes the setup `create_gfx` started
+    fn gfx_ready(&mut self, id: WindowId, mut gfx: Gfx<'static>) -> anyhow::Result<()> {
+        // closed before its device was ready
+        let Some(state) = self.windows.get_mut(&id) else {
+            return Ok(());
+        };
+
+        let scene = self.args.create_scene(&gfx.scene_context())?;
+        gfx.set_scene(scene);
+        if self.args.watch {
+            gfx.watch_shader(PathBuf::from(self.args.scene_kind().shader_path()))?;
+        }
+        for _ in 0..state.index {
+            gfx.next_clear_color();
+        }
+        state.gfx = Some(gfx);
+
+        // redraws requested before there was anything to draw with have been dropped
+        state.window.request_redraw();
+        Ok(())
+    }
+
+    fn exit_with(&mut self, event_loop: &ActiveEventLoop, err: anyhow::Error) {
+        // there's no `main` to hand the error back to on the web
+        #[cfg(target_arch = "wasm32")]
+        log::error!("{err:#}");
+        self.error = Some(err);
+        event_loop.exit();
+    }
+
+    fn redraw(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        let Some(state) = self.windows.get_mut(&id) else {
+            return;
+        };
+        if state.gfx.as_ref().is_some_and(Gfx::device_lost) {
+            log::warn!("recreating the device for {}", state.title);
+            // dropped first, a window can only have one surface at a time
+            state.gfx = None;
+            state.last_frame = None;
+            let window = state.window.clone();
+            self.create_gfx(id, window);
+            return;
+        }
+        if let Some(Err(err)) = state.gfx.as_ref().map(Gfx::uncaptured_error) {
+            self.exit_with(event_loop, err);
+            return;
+        }
+        let frame_time = match state.redraw() {
+            Ok(Some(frame_time)) => frame_time,
+            Ok(None) => return,
+            Err(err) => {
+                self.exit_with(event_loop, err);
+                return;
+            }
+        };
+
+        self.first_frame.get_or_insert_with(Instant::now);
+        // with more than one window, their frames all count towards the total
+        if let Some(bench) = self.bench.as_mut() {
+            if bench.frame(frame_time) {
+                println!("{bench}");
+                event_loop.exit();
+            }
+        }
+    }
+
+    /// remembers where the first window is, for the next run
+    fn save_placement(&self) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        let Some(placement) = self
+            .windows
+            .values()
+            .find(|state| state.index == 0)
+            .and_then(WindowState::placement)
+        else {
+            return;
+        };
+        if let Err(err) = placement.save() {
+            log::warn!("{err:#}");
+        }
+    }
+
+    /// drops the window's surface and device, the others keep running
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        if self.windows.get(&id).is_some_and(|state| state.index == 0) {
+            self.save_placement();
+        }
+        self.windows.remove(&id);
+        if self.windows.is_empty() {
+            event_loop.exit();
+        }
+    }
+}
+
+impl ApplicationHandler<GfxReady> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // only mobile platforms suspend and resume more than once
+        if !self.windows.is_empty() {
+            return;
+        }
+        if let Err(err) = self.init(event_loop) {
+            self.exit_with(event_loop, err);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        #[cfg(target_os = "linux")]
+        {
+            self.gamepads.poll(now);
+            let sticks = self.gamepads.sticks();
+            // like the keyboard, controllers only move the camera of the focused window
+            for state in self.windows.values_mut() {
+                state.sticks = match state.focused {
+                    true => sticks,
+                    false => [Vec2::ZERO; 2],
+                };
+            }
+        }
+        let mut wake_up = self
+            .windows
+            .values_mut()
+            .filter_map(|state| state.request_due_frame(now))
+            .min();
+
+        if let (Some(run_for), Some(first_frame)) = (self.args.run_for, self.first_frame) {
+            let deadline = first_frame + run_for;
+            // redraws stop while minimized, waking up at the deadline makes sure it's still kept
+            if now >= deadline {
+                log::info!("exiting after running for {run_for:?}");
+                event_loop.exit();
+                return;
+            }
+            wake_up = Some(wake_up.map_or(deadline, |wake_up| wake_up.min(deadline)));
+        }
+
+        event_loop.set_control_flow(wake_up.map_or(ControlFlow::Wait, ControlFlow::WaitUntil));
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        // device events don't belong to a window, the one with mouse look on takes them
+        if let DeviceEvent::MouseMotion { delta } = event {
+            for state in self.windows.values_mut() {
+                state.mouse_motion(delta);
+            }
+        }
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, GfxReady(id, gfx): GfxReady) {
+        if let Err(err) = gfx.and_then(|gfx| self.gfx_ready(id, gfx)) {
+            self.exit_with(event_loop, err);
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        match event {
+            WindowEvent::CloseRequested => {
+                self.close_window(event_loop, window_id);
+            }
+
+            // closes all of them
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => {
+                self.save_placement();
+                event_loop.exit();
+            }
+
+            WindowEvent::RedrawRequested => {
+                self.redraw(event_loop, window_id);
+            }
+
+            event => {
+                if let Some(state) = self.windows.get_mut(&window_id) {
+                    state.window_event(event);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::borrow::Cow;
+
+/// a vertical gradient drawn by a full-screen triangle in a pass of its own, the scene pass
+/// loads it instead of clearing
+pub struct Background {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Background {
+    /// `format` and `sample_count` are the scene's color target's, group 0 is the scene's too
+    /// for the resolution in the globals
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("background pipeline layout"),
+            bind_group_layouts: &[frame_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("background shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("background.wgsl"))),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("background pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+        Self { pipeline }
+    }
+
+    /// clears `view` and fills it with the gradient. it's the multisampled view when there is
+    /// one, which is stored unresolved for the scene pass to resolve
+    pub fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        frame_bind_group: &wgpu::BindGroup,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("background pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, frame_bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,30 @@
+// matches `Globals` in shader.wgsl, only the resolution is used
+struct Globals {
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    time: f32,
+    scale_factor: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+// the sky at the top of the scene and at its bottom, linear
+const ZENITH: vec3<f32> = vec3<f32>(0.05, 0.15, 0.45);
+const HORIZON: vec3<f32> = vec3<f32>(0.6, 0.75, 0.9);
+
+// a single triangle that covers the whole screen, like the one in blit.wgsl
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
+
+// only the color target, the scene pass clears the debug one
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // in framebuffer pixels from the top left, so 0 at the top and 1 at the bottom
+    let t = position.y / globals.resolution.y;
+    return vec4<f32>(mix(ZENITH, HORIZON, t), 1.0);
+}
@@ -0,0 +1,81 @@
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+// the scene's second render target
+@group(0) @binding(2)
+var debug: texture_2d<f32>;
+
+// matches `Grading` in offscreen.rs
+struct Grading {
+    gamma: f32,
+    exposure: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> grading: Grading;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// a single triangle that covers the whole screen, its corners outside of it are clipped
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// the scene was rendered in hdr, its colors are brought into [0, 1] before anything else
+override TONE_MAP: bool = false;
+
+// narkowicz's fit of the aces filmic curve
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    let numerator = x * (2.51 * x + 0.03);
+    let denominator = x * (2.43 * x + 0.59) + 0.14;
+    return clamp(numerator / denominator, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// tone mapped if needed and color corrected, what every effect but debug starts from
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    let color = textureSample(source, source_sampler, uv);
+    var rgb = color.rgb;
+    if TONE_MAP {
+        rgb = aces(rgb);
+    }
+    // `pow` is undefined for negative bases, the hdr texture can hold those
+    rgb = pow(max(rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / grading.gamma)) * grading.exposure;
+    return vec4<f32>(rgb, color.a);
+}
+
+// one entry point per `Effect`, the pixels are linear even when the target is sRGB
+
+@fragment
+fn fs_passthrough(in: VertexOutput) -> @location(0) vec4<f32> {
+    return sample_source(in.uv);
+}
+
+@fragment
+fn fs_grayscale(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    // relative luminance of linear rec. 709 primaries
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return vec4<f32>(vec3<f32>(luminance), color.a);
+}
+
+@fragment
+fn fs_debug(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(debug, source_sampler, in.uv);
+}
+
+@fragment
+fn fs_invert(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    return vec4<f32>(1.0 - color.rgb, color.a);
+}
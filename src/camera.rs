@@ -0,0 +1,102 @@
+use glam::{Mat4, Vec2, Vec3};
+
+/// vertical field of view in radians
+const FOV_Y: f32 = std::f32::consts::FRAC_PI_4;
+/// radians per pixel of mouse movement
+const ORBIT_SENSITIVITY: f32 = 0.005;
+/// keeps the eye off the poles, where `look_at` has no well defined up direction
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+/// units per second
+const FLY_SPEED: f32 = 1.5;
+/// what the distance is multiplied with per line scrolled
+const ZOOM_PER_LINE: f32 = 0.9;
+/// keeps the target between the near and the far plane
+const MIN_DISTANCE: f32 = 0.2;
+const MAX_DISTANCE: f32 = 50.0;
+
+/// maps pixels of a `width` x `height` target to clip space, with (0, 0) in the top left
+/// corner and y pointing down like the window's coordinates. anything between z -1 and 1 is
+/// visible
+pub fn pixel_projection(width: f32, height: f32) -> Mat4 {
+    // bottom and top swapped, clip space's y points up
+    Mat4::orthographic_rh(0.0, width, height, 0.0, -1.0, 1.0)
+}
+
+/// looks at `target` from a point on a sphere around it, dragging orbits around it and flying
+/// moves both along
+pub struct OrbitCamera {
+    target: Vec3,
+    /// rotation around the y axis, 0 looks down the negative z axis
+    yaw: f32,
+    /// elevation above the xz plane
+    pitch: f32,
+    distance: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            // far enough for the [-1, 1] square around the origin to fill the view vertically
+            distance: 1.0 / (FOV_Y / 2.0).tan(),
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// `dx` and `dy` are the cursor movement in pixels, dragging down looks at the scene from above
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        self.yaw = (self.yaw - dx * ORBIT_SENSITIVITY) % std::f32::consts::TAU;
+        self.pitch = (self.pitch + dy * ORBIT_SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// turns the view around the eye instead of the target, like looking around in a first
+    /// person game. `dx` and `dy` are the mouse movement, moving it down looks down
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        let eye = self.eye();
+        self.orbit(dx, dy);
+        // the eye moved around the target, the target has to move back around the eye instead
+        self.target = eye - (self.eye() - self.target);
+    }
+
+    /// moves the eye closer to the target for positive `lines`, each line by the same factor so
+    /// zooming feels the same close up and far away
+    pub fn zoom(&mut self, lines: f32) {
+        self.distance =
+            (self.distance * ZOOM_PER_LINE.powf(lines)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+
+    /// `direction` is x to the right and y forward in view space, it should be at most unit
+    /// length so moving diagonally isn't faster
+    pub fn fly(&mut self, direction: Vec2, dt: f32) {
+        let forward = (self.target - self.eye()).normalize();
+        let right = forward.cross(Vec3::Y).normalize();
+        self.target += (right * direction.x + forward * direction.y) * FLY_SPEED * dt;
+    }
+
+    /// a copy looking at the same target, orbited `yaw` radians further around it
+    pub fn turned(&self, yaw: f32) -> Self {
+        Self {
+            yaw: (self.yaw + yaw) % std::f32::consts::TAU,
+            ..*self
+        }
+    }
+
+    fn eye(&self) -> Vec3 {
+        self.target
+            + self.distance
+                * Vec3::new(
+                    self.pitch.cos() * self.yaw.sin(),
+                    self.pitch.sin(),
+                    self.pitch.cos() * self.yaw.cos(),
+                )
+    }
+
+    pub fn view_projection(&self, aspect: f32) -> Mat4 {
+        let view = Mat4::look_at_rh(self.eye(), self.target, Vec3::Y);
+        let projection = Mat4::perspective_rh(FOV_Y, aspect, 0.1, 100.0);
+        projection * view
+    }
+}
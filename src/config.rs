@@ -0,0 +1,215 @@
+use winit::dpi::PhysicalSize;
+
+use crate::PresentMode;
+// there's no file to read the config from on the web
+#[cfg(not(target_arch = "wasm32"))]
+use {anyhow::Context, clap::ValueEnum, std::path::Path};
+
+/// where the config is read from without `--config`, it's fine for it not to exist
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// settings from a toml file like
+///
+/// ```toml
+/// width = 1280
+/// height = 720
+/// clear_color = [0.1, 0.2, 0.3]
+/// present_mode = "mailbox"
+/// sample_count = 4
+/// backend = "vulkan"
+/// ```
+///
+/// everything is optional, anything missing keeps its built-in default. command line flags win
+/// over the file
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub window_size: Option<PhysicalSize<u32>>,
+    pub clear_color: Option<wgpu::Color>,
+    pub present_mode: Option<PresentMode>,
+    pub sample_count: Option<u32>,
+    pub backends: Option<wgpu::Backends>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Config {
+    /// `path` is `None` to try `DEFAULT_CONFIG_PATH`, which may be missing
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let source = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?,
+            None => match std::fs::read_to_string(DEFAULT_CONFIG_PATH) {
+                Ok(source) => source,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(Self::default())
+                }
+                Err(err) => Err(err).context(format!("failed to read {DEFAULT_CONFIG_PATH}"))?,
+            },
+        };
+        let path = path.unwrap_or(Path::new(DEFAULT_CONFIG_PATH));
+        Self::parse(&source).with_context(|| format!("invalid config {}", path.display()))
+    }
+
+    fn parse(source: &str) -> anyhow::Result<Self> {
+        let document = source.parse::<toml_edit::DocumentMut>()?;
+
+        let mut config = Self::default();
+        let (mut width, mut height) = (None, None);
+        for (key, item) in document.iter() {
+            match key {
+                "width" => width = Some(Self::dimension(key, item)?),
+                "height" => height = Some(Self::dimension(key, item)?),
+                "clear_color" => config.clear_color = Some(Self::color(key, item)?),
+                "present_mode" => {
+                    let name = Self::string(key, item)?;
+                    let mode = PresentMode::from_str(name, true).map_err(|_| {
+                        let names = PresentMode::value_variants()
+                            .iter()
+                            .filter_map(|mode| mode.to_possible_value())
+                            .map(|value| value.get_name().to_owned())
+                            .collect::<Vec<_>>();
+                        anyhow::anyhow!(
+                            "`present_mode` has to be one of {}, not {name:?}",
+                            names.join(", ")
+                        )
+                    })?;
+                    config.present_mode = Some(mode);
+                }
+                "sample_count" => config.sample_count = Some(Self::integer(key, item)?),
+                "backend" => {
+                    let names = Self::string(key, item)?;
+                    let backends =
+                        wgpu::util::parse_backends_from_comma_list(&names.to_lowercase());
+                    if backends.is_empty() {
+                        Err(anyhow::anyhow!(
+                            "`backend` {names:?} doesn't name a known backend"
+                        ))?;
+                    }
+                    config.backends = Some(backends);
+                }
+                _ => Err(anyhow::anyhow!("unknown setting `{key}`"))?,
+            }
+        }
+
+        config.window_size = match (width, height) {
+            (Some(width), Some(height)) => Some(PhysicalSize::new(width, height)),
+            (None, None) => None,
+            _ => Err(anyhow::anyhow!(
+                "`width` and `height` have to be set together"
+            ))?,
+        };
+        Ok(config)
+    }
+
+    fn string<'a>(key: &str, item: &'a toml_edit::Item) -> anyhow::Result<&'a str> {
+        item.as_str()
+            .ok_or_else(|| anyhow::anyhow!("`{key}` has to be a string"))
+    }
+
+    fn integer(key: &str, item: &toml_edit::Item) -> anyhow::Result<u32> {
+        item.as_integer()
+            .and_then(|value| u32::try_from(value).ok())
+            .ok_or_else(|| anyhow::anyhow!("`{key}` has to be a positive integer"))
+    }
+
+    fn dimension(key: &str, item: &toml_edit::Item) -> anyhow::Result<u32> {
+        match Self::integer(key, item)? {
+            0 => Err(anyhow::anyhow!("`{key}` can't be 0")),
+            value => Ok(value),
+        }
+    }
+
+    /// `[r, g, b]` or `[r, g, b, a]` in [0, 1]
+    fn color(key: &str, item: &toml_edit::Item) -> anyhow::Result<wgpu::Color> {
+        let error = || anyhow::anyhow!("`{key}` has to be an array of 3 or 4 numbers");
+        let components = item
+            .as_array()
+            .ok_or_else(error)?
+            .iter()
+            .map(|value| {
+                value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|value| value as f64))
+                    .ok_or_else(error)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        match components[..] {
+            [r, g, b] => Ok(wgpu::Color { r, g, b, a: 1.0 }),
+            [r, g, b, a] => Ok(wgpu::Color { r, g, b, a }),
+            _ => Err(error()),
+        }
+    }
+}
+
+// like the parser, native only
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_setting() {
+        let config = Config::parse(
+            r#"
+            width = 640
+            height = 480
+            clear_color = [0.1, 0.2, 0, 0.5]
+            present_mode = "Mailbox"
+            sample_count = 4
+            backend = "vulkan,gl"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.window_size, Some(PhysicalSize::new(640, 480)));
+        assert_eq!(
+            config.clear_color,
+            Some(wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.0,
+                a: 0.5,
+            })
+        );
+        assert!(matches!(config.present_mode, Some(PresentMode::Mailbox)));
+        assert_eq!(config.sample_count, Some(4));
+        assert_eq!(
+            config.backends,
+            Some(wgpu::Backends::VULKAN | wgpu::Backends::GL)
+        );
+    }
+
+    #[test]
+    fn missing_settings_keep_their_defaults() {
+        let config = Config::parse("clear_color = [1, 1, 1]").unwrap();
+
+        assert_eq!(config.clear_color, Some(wgpu::Color::WHITE));
+        assert_eq!(config.window_size, None);
+        assert!(config.present_mode.is_none());
+        assert_eq!(config.sample_count, None);
+        assert_eq!(config.backends, None);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for source in [
+            "width = 640",
+            "width = 0\nheight = 480",
+            "width = -1\nheight = 480",
+            "clear_color = [1, 1]",
+            "clear_color = \"red\"",
+            "present_mode = \"sometimes\"",
+            "sample_count = \"four\"",
+            "backend = \"opengl es\"",
+            "width = ",
+        ] {
+            assert!(Config::parse(source).is_err(), "{source:?} parsed");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let err = Config::parse("vsync = true").unwrap_err();
+
+        assert!(err.to_string().contains("`vsync`"), "{err}");
+    }
+}
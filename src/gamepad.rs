@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use glam::Vec2;
+use web_time::Instant;
+
+/// where the kernel puts the joystick devices, one `js<n>` per controller
+const DEVICE_DIR: &str = "/dev/input";
+/// how often `Gamepads::poll` looks for controllers that were plugged in
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+/// how far a stick has to be pushed before it counts, of its full range. worn sticks don't
+/// rest exactly in the middle
+const DEADZONE: f32 = 0.15;
+/// the axes of the left and the right stick, x and y, as the xpad and most other drivers
+/// number them. y is positive towards the player
+const STICK_AXES: [[u8; 2]; 2] = [[0, 1], [3, 4]];
+
+/// the kernel's `struct js_event`
+const EVENT_SIZE: usize = 8;
+const EVENT_AXIS: u8 = 0x02;
+/// set on the events describing the state right after the device is opened
+const EVENT_INIT: u8 = 0x80;
+
+enum Message {
+    Axis { path: PathBuf, axis: u8, value: i16 },
+    Disconnected(PathBuf),
+}
+
+/// game controllers through the linux joystick api. every device gets a thread blocking on its
+/// events, a device that can't be read anymore was unplugged
+pub struct Gamepads {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    /// the last value of every axis that moved, of every connected device
+    axes: HashMap<PathBuf, HashMap<u8, i16>>,
+    last_scan: Option<Instant>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            axes: HashMap::new(),
+            last_scan: None,
+        }
+    }
+
+    /// takes in what happened since the last call, and every `SCAN_INTERVAL` the controllers
+    /// that were connected in the meantime
+    pub fn poll(&mut self, now: Instant) {
+        if self
+            .last_scan
+            .is_none_or(|last_scan| now - last_scan >= SCAN_INTERVAL)
+        {
+            self.last_scan = Some(now);
+            self.scan();
+        }
+
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Message::Axis { path, axis, value } => {
+                    // a late event of a device that's gone already
+                    if let Some(axes) = self.axes.get_mut(&path) {
+                        axes.insert(axis, value);
+                    }
+                }
+                Message::Disconnected(path) => {
+                    log::info!("gamepad {} disconnected", path.display());
+                    self.axes.remove(&path);
+                }
+            }
+        }
+    }
+
+    fn scan(&mut self) {
+        let entries = match std::fs::read_dir(DEVICE_DIR) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::debug!("failed to list {DEVICE_DIR}: {err}");
+                return;
+            }
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            let joystick = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("js"));
+            if !joystick || self.axes.contains_key(&path) {
+                continue;
+            }
+            // most likely no permission, which won't change until the next login
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) => {
+                    log::debug!("failed to open {}: {err}", path.display());
+                    continue;
+                }
+            };
+            log::info!("gamepad {} connected", path.display());
+            self.axes.insert(path.clone(), HashMap::new());
+            let sender = self.sender.clone();
+            std::thread::spawn(move || read_events(file, path, sender));
+        }
+    }
+
+    /// the left and the right stick of all controllers together, each at most unit length with
+    /// x to the right and y forward, zero inside the deadzone
+    pub fn sticks(&self) -> [Vec2; 2] {
+        STICK_AXES.map(|[x, y]| {
+            self.axes
+                .values()
+                .map(|axes| {
+                    let axis = |axis| axes.get(&axis).copied().unwrap_or(0);
+                    stick(axis(x), axis(y))
+                })
+                .sum::<Vec2>()
+                .clamp_length_max(1.0)
+        })
+    }
+}
+
+/// runs until the device is gone or the app is
+fn read_events(mut file: File, path: PathBuf, sender: Sender<Message>) {
+    let mut event = [0; EVENT_SIZE];
+    while file.read_exact(&mut event).is_ok() {
+        let Some((axis, value)) = parse_axis(event) else {
+            continue;
+        };
+        let message = Message::Axis {
+            path: path.clone(),
+            axis,
+            value,
+        };
+        if sender.send(message).is_err() {
+            return;
+        }
+    }
+    let _ = sender.send(Message::Disconnected(path));
+}
+
+/// the axis and its value if `event` is an axis moving, buttons aren't used
+fn parse_axis(event: [u8; EVENT_SIZE]) -> Option<(u8, i16)> {
+    let [_, _, _, _, value @ .., kind, number] = event;
+    (kind & !EVENT_INIT == EVENT_AXIS).then_some((number, i16::from_ne_bytes(value)))
+}
+
+/// a stick's raw axes as x to the right and y forward, the deadzone is cut out of its range so
+/// it starts moving from zero right outside of it
+fn stick(x: i16, y: i16) -> Vec2 {
+    let raw = Vec2::new(f32::from(x), -f32::from(y)) / f32::from(i16::MAX);
+    let length = raw.length().min(1.0);
+    if length <= DEADZONE {
+        return Vec2::ZERO;
+    }
+    raw.normalize() * (length - DEADZONE) / (1.0 - DEADZONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(value: i16, kind: u8, number: u8) -> [u8; EVENT_SIZE] {
+        let mut event = [0; EVENT_SIZE];
+        event[..4].copy_from_slice(&1234u32.to_ne_bytes());
+        event[4..6].copy_from_slice(&value.to_ne_bytes());
+        event[6] = kind;
+        event[7] = number;
+        event
+    }
+
+    #[test]
+    fn parses_axis_events() {
+        assert_eq!(parse_axis(event(-300, EVENT_AXIS, 3)), Some((3, -300)));
+        // the state right after opening counts the same
+        assert_eq!(
+            parse_axis(event(i16::MAX, EVENT_AXIS | EVENT_INIT, 1)),
+            Some((1, i16::MAX))
+        );
+        // a button
+        assert_eq!(parse_axis(event(1, 0x01, 0)), None);
+    }
+
+    #[test]
+    fn ignores_the_deadzone() {
+        assert_eq!(stick(0, 0), Vec2::ZERO);
+        assert_eq!(stick(3000, -2000), Vec2::ZERO);
+    }
+
+    #[test]
+    fn maps_full_deflection_to_unit_length() {
+        assert_eq!(stick(i16::MAX, 0), Vec2::X);
+        // pushed away from the player is forward
+        assert_eq!(stick(0, -i16::MAX), Vec2::Y);
+        // the corners of the range are beyond the circle a stick moves in
+        let corner = stick(i16::MIN, i16::MIN);
+        assert!((corner.length() - 1.0).abs() < 1e-6);
+        assert!(corner.x < 0.0 && corner.y > 0.0);
+    }
+
+    #[test]
+    fn starts_at_zero_right_outside_the_deadzone() {
+        let edge = (DEADZONE * f32::from(i16::MAX)) as i16 + 1;
+        assert!(stick(edge, 0).x < 0.001);
+        let half = stick(i16::MAX / 2, 0).x;
+        assert!((half - (0.5 - DEADZONE) / (1.0 - DEADZONE)).abs() < 0.001);
+    }
+}
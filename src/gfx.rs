@@ -0,0 +1,2111 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+use glam::{Mat4, Vec2};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::WindowEvent,
+    window::Window,
+};
+
+use crate::{
+    background::Background,
+    camera::{self, OrbitCamera},
+    offscreen::{Effect, Grading, Offscreen},
+    panel::{DebugPanel, PanelValues},
+    scene::{EmptyScene, Scene, SceneContext},
+    skybox::Skybox,
+    spawn::Spawned,
+    text::TextRenderer,
+    timing::GpuTimer,
+};
+
+pub struct Gfx<'gfx> {
+    /// `None` when rendering headless
+    surface: Option<wgpu::Surface<'gfx>>,
+    device: wgpu::Device,
+    /// set by wgpu once the device is gone, e.g. after a driver reset. nothing rendered with it
+    /// shows up anymore, it takes a new `Gfx`
+    device_lost: Arc<AtomicBool>,
+    /// the first validation error nothing caught, with `exit_on_error`. it panics right away
+    /// without it
+    uncaptured_error: Arc<Mutex<Option<String>>>,
+    /// shared with the scene, which uploads its own uniforms
+    queue: Arc<wgpu::Queue>,
+    config: wgpu::SurfaceConfiguration,
+    /// what the surface supports, to switch between at runtime
+    present_modes: Vec<wgpu::PresentMode>,
+    scene: Box<dyn Scene>,
+    shader_watcher: Option<ShaderWatcher>,
+    /// set while the watched shader fails to compile, the scene is replaced by a red screen
+    shader_error: Option<String>,
+    depth_view: wgpu::TextureView,
+    /// of the scene's color targets, `HDR_FORMAT` when rendering in hdr and the target's
+    /// otherwise
+    scene_format: wgpu::TextureFormat,
+    sample_count: u32,
+    /// what every scene pipeline is created with
+    primitive: wgpu::PrimitiveState,
+    /// 1 if the adapter can't filter anisotropically
+    anisotropy: u16,
+    push_constants: bool,
+    /// passed on to the shader, 1 unless blending
+    opacity: f32,
+    msaa_view: Option<wgpu::TextureView>,
+    /// the scenes' second color target, resolved into `debug_view` with multisampling
+    debug_view: wgpu::TextureView,
+    debug_msaa_view: Option<wgpu::TextureView>,
+    /// the scene is rendered into this and copied to the target afterwards, if set
+    offscreen: Option<Offscreen>,
+    /// what the scene is rendered at regardless of the target's size, through `offscreen`.
+    /// `None` renders at the target's size
+    internal_size: Option<PhysicalSize<u32>>,
+    /// fraction of the scene's width and height that's drawn, centered. 1 draws all of it
+    scissor: f32,
+    /// width / height the scene keeps, with black bars around it where the target's differs.
+    /// `None` stretches the scene over the whole target
+    aspect: Option<f32>,
+    /// applied by `offscreen`, switching to anything but passthrough creates it
+    effect: Effect,
+    /// applied by `offscreen` too, changing it creates it
+    grading: Grading,
+    globals_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    /// group 0 of every scene pipeline
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    /// the right half of the scene, if it's split in two
+    split_screen: Option<SplitScreen>,
+    /// drawn instead of clearing to the clear color
+    background: Option<Background>,
+    skybox: Option<Skybox>,
+    /// the camera buffers hold `camera::pixel_projection` instead of the orbit camera's
+    pixel_space: bool,
+    /// the triangles clicked into the scene, if clicking spawns them
+    spawned: Option<Spawned>,
+    /// draws the hud and the debug panel over the finished frame
+    text: TextRenderer,
+    /// the framerate is drawn in the top left corner
+    hud: bool,
+    panel: DebugPanel,
+    /// multiplies the time the scene animates by, set with the debug panel
+    speed: f32,
+    /// the last one the app measured, `None` until a second has passed
+    fps: Option<f32>,
+    /// seconds of animation so far, only advanced by `update` so the clock stands still while
+    /// nothing is rendered
+    time: f32,
+    camera: OrbitCamera,
+    /// cycled through by clicking into the window
+    clear_colors: Vec<wgpu::Color>,
+    /// index into `clear_colors`
+    clear_color: usize,
+    /// last known cursor position, normalized to [0, 1] from the top left corner
+    mouse: [f32; 2],
+    /// of the window, passed on to the shader
+    scale_factor: f32,
+    screenshot_requested: bool,
+    pending_capture: Option<PendingCapture>,
+    /// the threads encoding screenshots, joined before `Gfx` is dropped so none is cut off
+    screenshot_writers: Vec<std::thread::JoinHandle<()>>,
+    /// pixel whose color is logged after the next rendered frame
+    pick_requested: Option<[u32; 2]>,
+    /// a single pixel capture, at the pixel in `PendingCapture::origin`
+    pending_pick: Option<PendingCapture>,
+    /// `None` if the adapter can't do timestamp queries
+    gpu_timer: Option<GpuTimer>,
+}
+
+/// a frame, or a part of it, copied into a mappable buffer, waiting for the gpu to finish the
+/// copy
+struct PendingCapture {
+    buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    /// top left corner of the copied part of the frame
+    origin: [u32; 2],
+    width: u32,
+    height: u32,
+    /// rows in `buffer` are padded to `COPY_BYTES_PER_ROW_ALIGNMENT`
+    padded_bytes_per_row: u32,
+    mapped: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+/// a second camera for the right half of a split screen, in its own group 0 with the same
+/// globals
+struct SplitScreen {
+    camera_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// how far the right half's camera is turned around the target from the left one's
+const SPLIT_SCREEN_YAW: f32 = std::f32::consts::FRAC_PI_4;
+
+/// tells when a shader file on disk changed
+struct ShaderWatcher {
+    path: PathBuf,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    // stops watching when dropped
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ShaderWatcher {
+    fn new(path: PathBuf) -> anyhow::Result<Self> {
+        use notify::Watcher;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        // editors tend to save by replacing the file, which a watch on the file itself misses
+        let directory = path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("{} has no parent directory", path.display()))?;
+        watcher.watch(directory, notify::RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            path,
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// drains all pending events, true if any of them touched the watched file
+    fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Ok(event) => {
+                    changed |= !event.kind.is_access()
+                        && event.paths.iter().any(|path| path.ends_with(&self.path))
+                }
+                Err(err) => log::warn!("error watching {}: {err}", self.path.display()),
+            }
+        }
+        changed
+    }
+}
+
+impl PendingCapture {
+    /// has to be called after the copy was submitted, mapping a buffer blocks its use in commands
+    fn start_readback(mut self) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.mapped = Some(receiver);
+        self
+    }
+
+    /// takes the capture out of `pending` once its buffer is mapped
+    fn take_mapped(pending: &mut Option<Self>) -> Option<Self> {
+        let result = pending.as_ref()?.mapped.as_ref()?.try_recv().ok()?;
+        let capture = pending.take()?;
+        if let Err(err) = result {
+            log::error!("failed to map the capture buffer: {err}");
+            return None;
+        }
+        Some(capture)
+    }
+
+    /// copies the mapped rows out without their padding and unmaps the buffer
+    fn take_pixels(&self) -> Vec<u8> {
+        let bytes_per_row = self.width * texel_size(self.format);
+        let pixels = {
+            let data = self.buffer.slice(..).get_mapped_range();
+            data.chunks(self.padded_bytes_per_row as usize)
+                .flat_map(|row| &row[..bytes_per_row as usize])
+                .copied()
+                .collect()
+        };
+        self.buffer.unmap();
+        pixels
+    }
+}
+
+const SCREENSHOT_PATH: &str = "screenshot.png";
+/// how long a blocking readback waits for its buffer to be mapped before giving up, the web
+/// can't block
+#[cfg(not(target_arch = "wasm32"))]
+const READBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// matches `Globals` in shader.wgsl, shadertoy's `iResolution`, `iMouse` and `iTime`.
+///
+/// uniforms follow wgsl's alignment rules, which are std140's for these types: an `f32` is
+/// aligned to 4 bytes, a `vec2` to 8, a `vec3` and a `vec4` to 16, and the whole struct's
+/// size rounds up to a multiple of 16. a `vec3` can't be followed by a `vec2` without a gap
+/// either. `_pad` rounds the 28 bytes up to 32, any other field needs its own padding in
+/// front of it to land where the shader reads it
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Globals {
+    /// framebuffer size in pixels
+    resolution: [f32; 2],
+    mouse: [f32; 2],
+    /// seconds of animation, stands still while paused
+    time: f32,
+    scale_factor: f32,
+    opacity: f32,
+    _pad: f32,
+}
+
+/// matches `Camera` in skybox.wgsl, a `mat4x4<f32>` is four `vec4` columns and needs no
+/// padding. the other shaders declare a `view_projection` uniform, which is its first matrix
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniform {
+    view_projection: [[f32; 4]; 4],
+    /// from clip space back to world space, for the skybox's view directions
+    inverse_view_projection: [[f32; 4]; 4],
+}
+
+impl From<Mat4> for CameraUniform {
+    fn from(view_projection: Mat4) -> Self {
+        Self {
+            view_projection: view_projection.to_cols_array_2d(),
+            inverse_view_projection: view_projection.inverse().to_cols_array_2d(),
+        }
+    }
+}
+
+/// the clear colors there are besides the configured one
+const CLEAR_COLORS: &[wgpu::Color] = &[
+    wgpu::Color::GREEN,
+    wgpu::Color::BLUE,
+    wgpu::Color::BLACK,
+    wgpu::Color::WHITE,
+    wgpu::Color {
+        r: 0.1,
+        g: 0.2,
+        b: 0.3,
+        a: 1.0,
+    },
+];
+
+/// with a stencil aspect, which masks the quads' outline
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+/// of the scenes' second color target
+pub const DEBUG_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+/// of the scene's color target with `--hdr`, linear and not clamped to [0, 1]
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+/// what the scenes can push, if the device has push constants
+pub const PUSH_CONSTANT_SIZE: u32 = 16;
+pub const DEFAULT_SIZE: PhysicalSize<u32> = PhysicalSize::new(1280, 720);
+
+/// how `Gfx` sets up the device and what it renders
+#[derive(Debug, Clone)]
+pub struct GfxOptions<'a> {
+    /// falls back to the highest count below it that the formats support
+    pub sample_count: u32,
+    /// falls back to fifo if the surface doesn't support it, unused headless
+    pub present_mode: wgpu::PresentMode,
+    /// the first adapter whose name contains this, wgpu chooses without one
+    pub adapter_name: Option<&'a str>,
+    /// directory to record an api trace into, for wgpu's player
+    pub trace: Option<&'a Path>,
+    /// which adapter wgpu prefers without `adapter_name`
+    pub power_preference: wgpu::PowerPreference,
+    /// only accept a software adapter, like lavapipe or WARP
+    pub force_fallback_adapter: bool,
+    pub wireframe: bool,
+    /// how the scenes' vertices are put together
+    pub topology: wgpu::PrimitiveTopology,
+    pub cull_mode: Option<wgpu::Face>,
+    /// which winding is the front for `cull_mode`
+    pub front_face: wgpu::FrontFace,
+    /// alpha of everything the scenes draw, they're opaque without it
+    pub opacity: Option<f32>,
+    pub srgb: bool,
+    /// render the scene in `HDR_FORMAT` and tone map it, onto a surface of the same format if
+    /// it supports one
+    pub hdr: bool,
+    /// `anisotropy_clamp` of the scenes' texture samplers, in [1, 16]
+    pub anisotropy: u16,
+    /// render through an `Offscreen` texture instead of directly into the target, any effect
+    /// but passthrough implies it
+    pub offscreen: bool,
+    /// render the scene at this fixed size and upscale it to the target, implies `offscreen`
+    pub internal_size: Option<PhysicalSize<u32>>,
+    /// width / height to letterbox the scene to, implies `offscreen`. the internal size's
+    /// without one, if set
+    pub aspect: Option<f32>,
+    pub effect: Effect,
+    /// gamma and exposure, anything but the default implies `offscreen`
+    pub grading: Grading,
+    /// draw the scene twice side by side, the right half from another angle
+    pub split_screen: bool,
+    /// draw a gradient behind the scene instead of the clear color
+    pub gradient: bool,
+    /// the directory with the faces of a cube map to draw behind the scene, see `Skybox::new`
+    pub skybox: Option<&'a Path>,
+    /// clicking spawns triangles instead of cycling the clear color
+    pub spawn: bool,
+    /// draw the framerate into the top left corner of the window
+    pub hud: bool,
+    /// project pixel coordinates instead of using the orbit camera
+    pub pixel_space: bool,
+    pub max_limits: bool,
+    /// requested instead of `wgpu::Limits::default()`, `max_limits` takes precedence
+    pub limits: Option<wgpu::Limits>,
+    /// required on top of the ones `Gfx` enables itself when the adapter has them, device
+    /// creation fails without these
+    pub features: wgpu::Features,
+    /// exit with the first uncaught wgpu error instead of panicking on it
+    pub exit_on_error: bool,
+    /// `WGPU_BACKEND` takes precedence, all backends are tried without either
+    pub backends: Option<wgpu::Backends>,
+    /// the first clear color, before the built-in ones
+    pub clear_color: Option<wgpu::Color>,
+}
+
+impl Default for GfxOptions<'_> {
+    fn default() -> Self {
+        Self {
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            present_mode: wgpu::PresentMode::Fifo,
+            adapter_name: None,
+            trace: None,
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            wireframe: false,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            front_face: wgpu::FrontFace::Ccw,
+            opacity: None,
+            srgb: true,
+            hdr: false,
+            anisotropy: 1,
+            offscreen: false,
+            internal_size: None,
+            aspect: None,
+            effect: Effect::Passthrough,
+            grading: Grading::default(),
+            split_screen: false,
+            gradient: false,
+            skybox: None,
+            spawn: false,
+            hud: false,
+            pixel_space: false,
+            max_limits: false,
+            limits: None,
+            features: wgpu::Features::empty(),
+            exit_on_error: false,
+            backends: None,
+            clear_color: None,
+        }
+    }
+}
+
+/// sets `GfxOptions` one at a time and creates the `Gfx` with them, starting from the defaults
+/// or from options put together elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct GfxBuilder<'a> {
+    options: GfxOptions<'a>,
+}
+
+impl<'a> From<GfxOptions<'a>> for GfxBuilder<'a> {
+    fn from(options: GfxOptions<'a>) -> Self {
+        Self { options }
+    }
+}
+
+impl<'a> GfxBuilder<'a> {
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.options.present_mode = present_mode;
+        self
+    }
+
+    /// samples per pixel, see `GfxOptions::sample_count`
+    pub fn msaa(mut self, sample_count: u32) -> Self {
+        self.options.sample_count = sample_count;
+        self
+    }
+
+    pub fn clear_color(mut self, color: wgpu::Color) -> Self {
+        self.options.clear_color = Some(color);
+        self
+    }
+
+    /// adds to the features required so far
+    pub fn features(mut self, features: wgpu::Features) -> Self {
+        self.options.features |= features;
+        self
+    }
+
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.options.limits = Some(limits);
+        self
+    }
+
+    /// sets up the device and a surface for `window`
+    pub async fn build(self, window: Arc<Window>) -> anyhow::Result<Gfx<'static>> {
+        let options = &self.options;
+        let size = window.as_ref().inner_size();
+
+        let instance = Gfx::create_instance(options.backends);
+
+        let surface = instance.create_surface(window.clone())?;
+        let adapter = Gfx::request_adapter(&instance, Some(&surface), options).await?;
+
+        let swapchain_capabilities = surface.get_capabilities(&adapter);
+        let mut config = surface
+            .get_default_config(&adapter, size.width, size.height)
+            .ok_or_else(|| anyhow::anyhow!("failed to create config!"))?;
+        config.format = Gfx::choose_format(&swapchain_capabilities.formats, options.srgb);
+        if options.hdr {
+            match swapchain_capabilities.formats.contains(&HDR_FORMAT) {
+                true => config.format = HDR_FORMAT,
+                false => {
+                    log::warn!("the surface doesn't support {HDR_FORMAT:?}, rendering without hdr")
+                }
+            }
+        }
+        // fifo is the only mode every surface has to support
+        let present_mode = options.present_mode;
+        config.present_mode = if swapchain_capabilities.present_modes.contains(&present_mode) {
+            present_mode
+        } else {
+            log::warn!("present mode {present_mode:?} is not supported, falling back to Fifo");
+            wgpu::PresentMode::Fifo
+        };
+        log::info!("using present mode {:?}", config.present_mode);
+
+        let mut gfx = Gfx::with_adapter(&adapter, Some(surface), config, options).await?;
+        gfx.present_modes = swapchain_capabilities.present_modes;
+        gfx.scale_factor = window.scale_factor() as f32;
+        Ok(gfx)
+    }
+
+    /// renders into offscreen textures only, works without a window or even a display
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn build_headless(self, size: PhysicalSize<u32>) -> anyhow::Result<Gfx<'static>> {
+        let options = &self.options;
+        let instance = Gfx::create_instance(options.backends);
+        let adapter = Gfx::request_adapter(&instance, None, options).await?;
+
+        // there is no surface to configure, but the rest of `Gfx` uses the config to know the
+        // size and format of what it renders to
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: match options.srgb {
+                true => wgpu::TextureFormat::Rgba8UnormSrgb,
+                false => wgpu::TextureFormat::Rgba8Unorm,
+            },
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        Gfx::with_adapter(&adapter, None, config, options).await
+    }
+}
+
+impl<'gfx> Gfx<'gfx> {
+    /// with the default options. the app configures its own through `GfxBuilder`, so nothing
+    /// here calls this
+    #[allow(dead_code)]
+    pub async fn new(window: Arc<Window>) -> anyhow::Result<Gfx<'static>> {
+        GfxBuilder::default().build(window).await
+    }
+
+    /// the order of `formats` is up to the platform, and picking the first one can mean
+    /// colors end up washed out or too dark depending on whether it's sRGB or not
+    fn choose_format(formats: &[wgpu::TextureFormat], srgb: bool) -> wgpu::TextureFormat {
+        let preferred = match srgb {
+            true => [
+                wgpu::TextureFormat::Bgra8UnormSrgb,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+            ],
+            false => [
+                wgpu::TextureFormat::Bgra8Unorm,
+                wgpu::TextureFormat::Rgba8Unorm,
+            ],
+        };
+        preferred
+            .into_iter()
+            .find(|format| formats.contains(format))
+            .or_else(|| {
+                formats
+                    .iter()
+                    .copied()
+                    .find(|format| format.is_srgb() == srgb)
+            })
+            .unwrap_or(formats[0])
+    }
+
+    /// honors `WGPU_BACKEND` (e.g. `vulkan`, `dx12`, `metal`, `gl`, or a comma separated list)
+    /// over `backends`
+    pub fn create_instance(backends: Option<wgpu::Backends>) -> wgpu::Instance {
+        let fallback = backends.unwrap_or(wgpu::Backends::all());
+        let backends = match wgpu::util::backend_bits_from_env() {
+            Some(backends) if !backends.is_empty() => backends,
+            Some(_) => {
+                log::warn!("WGPU_BACKEND doesn't name any known backend, ignoring it");
+                fallback
+            }
+            None => fallback,
+        };
+
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        })
+    }
+
+    /// picks the first adapter whose name contains `adapter_name`, or lets wgpu choose without
+    /// one
+    pub async fn request_adapter(
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+        options: &GfxOptions<'_>,
+    ) -> anyhow::Result<wgpu::Adapter> {
+        let adapter = match options.adapter_name {
+            // browsers only hand out the adapter they picked
+            #[cfg(target_arch = "wasm32")]
+            Some(_) => Err(anyhow::anyhow!(
+                "choosing an adapter isn't supported on the web"
+            ))?,
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(name) => {
+                let adapters = instance.enumerate_adapters(wgpu::Backends::all());
+                let names = adapters
+                    .iter()
+                    .map(|adapter| adapter.get_info().name)
+                    .collect::<Vec<_>>();
+                adapters
+                    .into_iter()
+                    .find(|adapter| {
+                        adapter.get_info().name.contains(name)
+                            && compatible_surface
+                                .is_none_or(|surface| adapter.is_surface_supported(surface))
+                    })
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "no adapter matches {name:?}, available: {}",
+                            names.join(", ")
+                        )
+                    })?
+            }
+            None => {
+                log::info!(
+                    "requesting {} adapter with power preference {:?}",
+                    match options.force_fallback_adapter {
+                        true => "a fallback",
+                        false => "an",
+                    },
+                    options.power_preference
+                );
+                instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference: options.power_preference,
+                        force_fallback_adapter: options.force_fallback_adapter,
+                        compatible_surface,
+                    })
+                    .await
+                    .ok_or_else(|| match options.force_fallback_adapter {
+                        true => anyhow::anyhow!("no fallback adapter available"),
+                        false => anyhow::anyhow!("no adapter found!"),
+                    })?
+            }
+        };
+        let info = adapter.get_info();
+        log::info!(
+            "using adapter {} (vendor {:#06x}, {:?}) on {:?}, driver {} {}",
+            info.name,
+            info.vendor,
+            info.device_type,
+            info.backend,
+            info.driver,
+            info.driver_info,
+        );
+        Ok(adapter)
+    }
+
+    async fn with_adapter(
+        adapter: &wgpu::Adapter,
+        surface: Option<wgpu::Surface<'gfx>>,
+        config: wgpu::SurfaceConfiguration,
+        options: &GfxOptions<'_>,
+    ) -> anyhow::Result<Self> {
+        let wireframe = options.wireframe;
+        // requesting a feature the adapter doesn't have would fail device creation
+        let wireframe_supported = adapter
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+        if wireframe && !wireframe_supported {
+            log::warn!("adapter doesn't support wireframe rendering, drawing filled");
+        }
+        let (polygon_mode, mut required_features) = if wireframe && wireframe_supported {
+            (wgpu::PolygonMode::Line, wgpu::Features::POLYGON_MODE_LINE)
+        } else {
+            (wgpu::PolygonMode::Fill, wgpu::Features::empty())
+        };
+
+        // not a feature but a downlevel capability, wgpu quietly samples with 1 without it
+        let anisotropy = if adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+        {
+            options.anisotropy
+        } else {
+            if options.anisotropy > 1 {
+                log::warn!("adapter doesn't support anisotropic filtering, sampling without it");
+            }
+            1
+        };
+
+        let timing_supported = adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+        if timing_supported {
+            required_features |= wgpu::Features::TIMESTAMP_QUERY;
+        } else {
+            log::info!("adapter doesn't support timestamp queries, gpu frame timing is disabled");
+        }
+
+        let missing = options.features.difference(adapter.features());
+        if !missing.is_empty() {
+            Err(anyhow::anyhow!(
+                "adapter doesn't support the requested features: {missing:?}"
+            ))?;
+        }
+        required_features |= options.features;
+
+        // the defaults are what every desktop adapter supports, the adapter's own maxima allow
+        // for larger textures and buffers
+        let mut required_limits = match (options.max_limits, &options.limits) {
+            (true, _) => adapter.limits(),
+            (false, Some(limits)) => limits.clone(),
+            (false, None) => wgpu::Limits::default(),
+        };
+
+        // the default limit is 0 bytes, so the feature alone isn't enough
+        let push_constants = adapter.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && adapter.limits().max_push_constant_size >= PUSH_CONSTANT_SIZE;
+        if push_constants {
+            required_features |= wgpu::Features::PUSH_CONSTANTS;
+            required_limits.max_push_constant_size = required_limits
+                .max_push_constant_size
+                .max(PUSH_CONSTANT_SIZE);
+        } else {
+            log::warn!("adapter doesn't support push constants, falling back to uniform buffers");
+        }
+        // `request_device` would fail on these too, but without saying which limit it was
+        let mut exceeded = Vec::new();
+        required_limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |name, requested, allowed| {
+                exceeded.push(format!(
+                    "{name} (requested {requested}, supported {allowed})"
+                ))
+            },
+        );
+        if !exceeded.is_empty() {
+            Err(anyhow::anyhow!(
+                "adapter doesn't support the requested limits: {}",
+                exceeded.join(", ")
+            ))?;
+        }
+
+        if let Some(trace) = options.trace {
+            use anyhow::Context;
+
+            std::fs::create_dir_all(trace)
+                .with_context(|| format!("failed to create {}", trace.display()))?;
+            log::info!("recording an api trace into {}", trace.display());
+        }
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("device"),
+                    required_features,
+                    required_limits,
+                    memory_hints: wgpu::MemoryHints::MemoryUsage,
+                },
+                options.trace,
+            )
+            .await?;
+        log::info!("using device limits {:#?}", device.limits());
+        let (device_lost, uncaptured_error) =
+            Self::watch_device_errors(&device, options.exit_on_error);
+
+        let globals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("globals"),
+            size: std::mem::size_of::<Globals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let create_camera_buffer = || {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("camera"),
+                size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+        let camera_buffer = create_camera_buffer();
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("frame bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    // the skybox unprojects its pixels in the fragment shader
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let create_bind_group = |camera_buffer: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("frame bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: globals_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: camera_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let bind_group = create_bind_group(&camera_buffer);
+        let split_screen = options.split_screen.then(|| {
+            let camera_buffer = create_camera_buffer();
+            SplitScreen {
+                bind_group: create_bind_group(&camera_buffer),
+                camera_buffer,
+            }
+        });
+
+        let swapchain_format = config.format;
+        log::info!("using swapchain format {swapchain_format:?}");
+        // `new` fell back to the regular formats if the surface can't show hdr, the scene is
+        // tone mapped by `offscreen` on its way to the target either way
+        let scene_format = match options.hdr {
+            true => {
+                log::info!("rendering in {HDR_FORMAT:?}, tone mapped to the target");
+                HDR_FORMAT
+            }
+            false => swapchain_format,
+        };
+
+        // all attachments are multisampled, so all of them need to support it. every format
+        // supports a single sample
+        let requested = options.sample_count;
+        let sample_count = [16, 8, 4, 2, 1]
+            .into_iter()
+            .filter(|count| *count <= requested)
+            .find(|count| {
+                [scene_format, DEBUG_FORMAT, DEPTH_FORMAT]
+                    .iter()
+                    .all(|format| {
+                        adapter
+                            .get_texture_format_features(*format)
+                            .flags
+                            .sample_count_supported(*count)
+                    })
+            })
+            .unwrap_or(1);
+        if sample_count != requested {
+            log::warn!(
+                "{requested}x multisampling is not supported, rendering with {sample_count}x"
+            );
+        }
+        log::info!("using {sample_count}x multisampling");
+
+        if let Some(surface) = surface.as_ref() {
+            surface.configure(&device, &config);
+        }
+
+        let internal_size = options.internal_size;
+        if let Some(size) = internal_size {
+            let max = device.limits().max_texture_dimension_2d;
+            if size.width > max || size.height > max {
+                Err(anyhow::anyhow!(
+                    "internal resolution {}x{} is too large, the device supports at most {max}x{max}",
+                    size.width,
+                    size.height,
+                ))?;
+            }
+        }
+        let aspect = options
+            .aspect
+            .or(internal_size.map(|size| size.width as f32 / size.height as f32));
+        let scene_size = internal_size
+            .unwrap_or(letterbox(PhysicalSize::new(config.width, config.height), aspect).1);
+
+        let depth_view = Self::create_depth_view(&device, scene_size, sample_count);
+        let msaa_view = Self::create_msaa_view(&device, scene_size, scene_format, sample_count);
+        let debug_view = Self::create_debug_view(&device, scene_size);
+        let debug_msaa_view =
+            Self::create_msaa_view(&device, scene_size, DEBUG_FORMAT, sample_count);
+        let offscreen = (options.offscreen
+            || options.effect != Effect::Passthrough
+            || options.grading != Grading::default()
+            || aspect.is_some()
+            || scene_format == HDR_FORMAT)
+            .then(|| {
+                Offscreen::new(
+                    &device,
+                    scene_format,
+                    swapchain_format,
+                    scene_size.width,
+                    scene_size.height,
+                    &debug_view,
+                    options.grading,
+                )
+            });
+        let background = options
+            .gradient
+            .then(|| Background::new(&device, scene_format, sample_count, &bind_group_layout));
+        let skybox = options
+            .skybox
+            .map(|directory| {
+                Skybox::new(
+                    &device,
+                    &queue,
+                    scene_format,
+                    sample_count,
+                    &bind_group_layout,
+                    directory,
+                )
+            })
+            .transpose()?;
+        let spawned = options
+            .spawn
+            .then(|| Spawned::new(&device, scene_format, sample_count));
+        let text = TextRenderer::new(
+            &device,
+            &queue,
+            config.format,
+            PhysicalSize::new(config.width, config.height),
+        );
+        let queue = Arc::new(queue);
+        let gpu_timer = timing_supported.then(|| GpuTimer::new(&device, &queue));
+
+        Ok(Self {
+            surface,
+            device,
+            device_lost,
+            uncaptured_error,
+            queue,
+            present_modes: vec![config.present_mode],
+            config,
+            scene: Box::new(EmptyScene),
+            shader_watcher: None,
+            shader_error: None,
+            depth_view,
+            scene_format,
+            sample_count,
+            primitive: wgpu::PrimitiveState {
+                topology: options.topology,
+                front_face: options.front_face,
+                cull_mode: options.cull_mode,
+                polygon_mode,
+                ..Default::default()
+            },
+            anisotropy,
+            push_constants,
+            opacity: options.opacity.unwrap_or(1.0),
+            msaa_view,
+            debug_view,
+            debug_msaa_view,
+            offscreen,
+            internal_size,
+            aspect,
+            scissor: 1.0,
+            effect: options.effect,
+            grading: options.grading,
+            globals_buffer,
+            camera_buffer,
+            bind_group_layout,
+            bind_group,
+            split_screen,
+            background,
+            skybox,
+            pixel_space: options.pixel_space,
+            spawned,
+            text,
+            hud: options.hud,
+            panel: DebugPanel::new(),
+            speed: 1.0,
+            fps: None,
+            time: 0.0,
+            camera: OrbitCamera::default(),
+            clear_colors: options
+                .clear_color
+                .into_iter()
+                .chain(CLEAR_COLORS.iter().copied())
+                .collect(),
+            clear_color: 0,
+            mouse: [0.5, 0.5],
+            scale_factor: 1.0,
+            screenshot_requested: false,
+            pending_capture: None,
+            screenshot_writers: Vec::new(),
+            pick_requested: None,
+            pending_pick: None,
+            gpu_timer,
+        })
+    }
+
+    /// whether the device is lost and the first uncaught error, see `Gfx::uncaptured_error`
+    fn watch_device_errors(
+        device: &wgpu::Device,
+        exit_on_error: bool,
+    ) -> (Arc<AtomicBool>, Arc<Mutex<Option<String>>>) {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let uncaptured_error = Arc::new(Mutex::new(None));
+
+        device.set_device_lost_callback({
+            let device_lost = device_lost.clone();
+            move |reason, message| {
+                // wgpu also calls this when `Gfx` drops the device
+                if reason == wgpu::DeviceLostReason::Dropped {
+                    return;
+                }
+                log::error!("lost the device ({reason:?}): {message}");
+                device_lost.store(true, Ordering::Relaxed);
+            }
+        });
+
+        // the default handler panics, which is still right for our own mistakes. whatever fails
+        // on a lost device until it's replaced isn't one of those. the handler runs wherever
+        // wgpu notices the error, which can be on another thread, so exiting only records it
+        // for the event loop to pick up
+        device.on_uncaptured_error(Box::new({
+            let device_lost = device_lost.clone();
+            let uncaptured_error = uncaptured_error.clone();
+            move |err| {
+                if device_lost.load(Ordering::Relaxed) {
+                    log::warn!("ignoring an error on the lost device: {err}");
+                    return;
+                }
+                // the message already lists the failing call and the labels of what it used
+                log::error!("unhandled wgpu error: {err}");
+                if !exit_on_error {
+                    panic!("unhandled wgpu error: {err}");
+                }
+                uncaptured_error
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(|| err.to_string());
+            }
+        }));
+
+        (device_lost, uncaptured_error)
+    }
+
+    /// the device is gone and this has to be replaced by a new `Gfx`
+    pub fn device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// fails with the first uncaught wgpu error if there was one since `Gfx` was created, only
+    /// ever with `exit_on_error`
+    pub fn uncaptured_error(&self) -> anyhow::Result<()> {
+        match self.uncaptured_error.lock().unwrap().as_ref() {
+            Some(message) => Err(anyhow::anyhow!("unhandled wgpu error: {message}")),
+            None => Ok(()),
+        }
+    }
+
+    /// what the next scene is created with
+    pub fn scene_context(&self) -> SceneContext<'_> {
+        SceneContext {
+            device: &self.device,
+            queue: &self.queue,
+            format: self.scene_format,
+            sample_count: self.sample_count,
+            primitive: self.primitive,
+            debug_format: DEBUG_FORMAT,
+            anisotropy: self.anisotropy,
+            push_constants: self.push_constants,
+            pixel_space: self.pixel_space,
+            size: self.scene_size(),
+            // the surface stays opaque, the clear color is what shows through
+            blend: (self.opacity < 1.0).then_some(wgpu::BlendState::ALPHA_BLENDING),
+            frame_bind_group_layout: &self.bind_group_layout,
+        }
+    }
+
+    /// replaces what's drawn, `scene` has to be created from this `scene_context`
+    pub fn set_scene(&mut self, scene: Box<dyn Scene>) {
+        self.scene = scene;
+    }
+
+    /// starts loading the shader from `path` instead of the one built into the binary, and
+    /// reloads it whenever the file changes
+    pub fn watch_shader(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        self.shader_watcher = Some(ShaderWatcher::new(path)?);
+        self.reload_shader();
+        Ok(())
+    }
+
+    /// rebuilds the pipeline if the watched shader changed since the last call
+    pub fn poll_shader_watcher(&mut self) {
+        let changed = self
+            .shader_watcher
+            .as_ref()
+            .is_some_and(ShaderWatcher::changed);
+        if changed {
+            self.reload_shader();
+        }
+    }
+
+    /// the window turns red while the shader is broken, until it compiles again
+    fn reload_shader(&mut self) {
+        let Some(path) = self
+            .shader_watcher
+            .as_ref()
+            .map(|watcher| watcher.path.clone())
+        else {
+            return;
+        };
+
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                log::error!("failed to read {}: {err}", path.display());
+                return;
+            }
+        };
+
+        // the scene needs the context, which borrows all of `self`
+        let mut scene = std::mem::replace(&mut self.scene, Box::new(EmptyScene));
+        // validation errors would otherwise end up in the default handler, which panics
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let reloaded = scene.reload_shader(&self.scene_context(), &source);
+        // on native the future is ready right away
+        let validation = pollster::block_on(self.device.pop_error_scope());
+        let error = match (reloaded, validation) {
+            (Err(err), _) => Some(format!("{err:#}")),
+            // the description holds the full, formatted shader diagnostic
+            (Ok(()), Some(wgpu::Error::Validation { description, .. })) => Some(description),
+            (Ok(()), Some(err)) => Some(err.to_string()),
+            (Ok(()), None) => None,
+        };
+        match error {
+            Some(message) => {
+                log::error!("failed to reload {}: {message}", path.display());
+                self.shader_error = Some(message);
+            }
+            None => {
+                log::info!("reloaded {}", path.display());
+                self.shader_error = None;
+            }
+        }
+        self.scene = scene;
+    }
+
+    /// where in the target the scene ends up, the whole target unless it's letterboxed
+    fn viewport(&self) -> (PhysicalPosition<u32>, PhysicalSize<u32>) {
+        letterbox(
+            PhysicalSize::new(self.config.width, self.config.height),
+            self.aspect,
+        )
+    }
+
+    /// x, y, width and height in the scene's render targets of its left and right half. the left
+    /// one covers all of them unless the screen is split
+    fn split_viewports(&self) -> [[f32; 4]; 2] {
+        let size = self.scene_size();
+        let (width, height) = (size.width as f32, size.height as f32);
+        match self.split_screen {
+            // the right half gets the odd pixel
+            Some(_) => {
+                let left = (size.width / 2) as f32;
+                [[0.0, 0.0, left, height], [left, 0.0, width - left, height]]
+            }
+            None => [[0.0, 0.0, width, height]; 2],
+        }
+    }
+
+    /// of the scene's render targets, the viewport's unless there's a fixed internal size
+    fn scene_size(&self) -> PhysicalSize<u32> {
+        self.internal_size.unwrap_or(self.viewport().1)
+    }
+
+    /// advances the animation by `dt` seconds and uploads the uniforms for the next frame
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        let scene_size = self.scene_size();
+        let globals = Globals {
+            resolution: [scene_size.width as f32, scene_size.height as f32],
+            mouse: self.mouse,
+            time: self.time,
+            scale_factor: self.scale_factor,
+            opacity: self.opacity,
+            _pad: 0.0,
+        };
+        self.queue
+            .write_buffer(&self.globals_buffer, 0, bytemuck::bytes_of(&globals));
+
+        let [left, right] = self.split_viewports();
+        // in pixel space both halves start at their own top left corner, the viewport moves
+        // the right one over
+        let view_projection = |[_, _, width, height]: [f32; 4], yaw| match self.pixel_space {
+            true => camera::pixel_projection(width, height),
+            false => self.camera.turned(yaw).view_projection(width / height),
+        };
+        self.write_camera(&self.camera_buffer, view_projection(left, 0.0));
+        if let Some(split_screen) = self.split_screen.as_ref() {
+            self.write_camera(
+                &split_screen.camera_buffer,
+                view_projection(right, SPLIT_SCREEN_YAW),
+            );
+        }
+
+        self.scene.update(dt * self.speed);
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        // a minimized window reports a size of 0x0, which is not a valid surface size
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.reconfigure();
+        self.text.resize(&self.queue, new_size);
+        // at a fixed internal size only the upscaled copy onto the target changes
+        if self.internal_size.is_some() {
+            return;
+        }
+
+        let scene_size = self.scene_size();
+        self.depth_view = Self::create_depth_view(&self.device, scene_size, self.sample_count);
+        self.msaa_view = Self::create_msaa_view(
+            &self.device,
+            scene_size,
+            self.scene_format,
+            self.sample_count,
+        );
+        self.debug_view = Self::create_debug_view(&self.device, scene_size);
+        self.debug_msaa_view =
+            Self::create_msaa_view(&self.device, scene_size, DEBUG_FORMAT, self.sample_count);
+        if let Some(offscreen) = self.offscreen.as_mut() {
+            offscreen.resize(
+                &self.device,
+                scene_size.width,
+                scene_size.height,
+                &self.debug_view,
+            );
+        }
+
+        // the scene needs the context, which borrows all of `self`
+        let mut scene = std::mem::replace(&mut self.scene, Box::new(EmptyScene));
+        scene.resize(&self.scene_context());
+        self.scene = scene;
+    }
+
+    /// draws and presents the next frame, surface errors are left to the caller since whether
+    /// to reconfigure, skip or give up depends on the error
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let surface = self.surface.as_ref().expect("render needs a surface");
+        let frame = surface.get_current_texture()?;
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.draw(&view);
+        frame.present();
+        self.poll_capture();
+        Ok(())
+    }
+
+    /// encodes and submits the scene into `view`, plus the screenshot and pick captures of it
+    /// that were requested, and starts reading them back
+    fn draw(&mut self, view: &wgpu::TextureView) {
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            self.device.poll(wgpu::Maintain::Poll);
+            gpu_timer.poll();
+        }
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("frame encoder"),
+            });
+
+        self.encode_scene(&mut encoder, view, true);
+        self.encode_overlay(&mut encoder, view);
+        if let Some(gpu_timer) = self.gpu_timer.as_ref() {
+            gpu_timer.resolve(&mut encoder);
+        }
+        // only one capture of each kind in flight at a time
+        let capture = (self.screenshot_requested && self.pending_capture.is_none()).then(|| {
+            self.screenshot_requested = false;
+            self.encode_capture(&mut encoder, None)
+        });
+        let pick = self
+            .pick_requested
+            .filter(|_| self.pending_pick.is_none())
+            .map(|pixel| {
+                self.pick_requested = None;
+                self.encode_capture(&mut encoder, Some(pixel))
+            });
+
+        self.queue.submit(Some(encoder.finish()));
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.start_readback();
+        }
+        if let Some(capture) = capture {
+            self.pending_capture = Some(capture.start_readback());
+        }
+        if let Some(pick) = pick {
+            self.pending_pick = Some(pick.start_readback());
+        }
+    }
+
+    /// whole pixels of the hud's and the panel's font, at least 2 of them on a regular monitor
+    fn overlay_scale(&self) -> f32 {
+        (2.0 * self.scale_factor).round().max(1.0)
+    }
+
+    fn fps_text(&self) -> String {
+        match self.fps {
+            Some(fps) => format!("{fps:.0} FPS  {:.2} ms", 1000.0 / fps),
+            None => "-- FPS".to_string(),
+        }
+    }
+
+    /// the framerate in the top left corner of `view` and the debug panel in the top right
+    /// one, over the finished frame. captures render the scene on their own, so neither is part
+    /// of screenshots
+    fn encode_overlay(&mut self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let scale = self.overlay_scale();
+        if self.hud {
+            let text = self.fps_text();
+            let margin = Vec2::splat(3.0 * scale);
+            // readable over any clear color
+            self.text.rect(
+                Vec2::ZERO,
+                TextRenderer::size(&text, scale) + margin * 2.0,
+                [0.0, 0.0, 0.0, 0.6],
+            );
+            self.text.text(margin, scale, [1.0; 4], &text);
+        }
+        let size = PhysicalSize::new(self.config.width, self.config.height);
+        let (values, fps) = (self.panel_values(), self.fps_text());
+        self.panel.draw(&mut self.text, size, scale, values, &fps);
+        self.text.encode(&self.device, &self.queue, encoder, view);
+    }
+
+    /// renders a single frame offscreen and blocks until it's written to `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_frame(&mut self, path: &Path) -> anyhow::Result<()> {
+        // there's no surface texture to draw into, the capture renders the scene on its own
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("save frame encoder"),
+            });
+        let capture = self.encode_capture(&mut encoder, None);
+        self.queue.submit(Some(encoder.finish()));
+
+        let pixels = self.read_capture_blocking(&capture)?;
+        save_png(path, pixels, capture.width, capture.height, capture.format)
+    }
+
+    /// renders a single frame offscreen and blocks until the color of `pixel` in it is read
+    /// back, as rgba
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_pixel(&mut self, pixel: [u32; 2]) -> anyhow::Result<[u8; 4]> {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("read pixel encoder"),
+            });
+        let capture = self.encode_capture(&mut encoder, Some(pixel));
+        self.queue.submit(Some(encoder.finish()));
+
+        let pixels = to_rgba(self.read_capture_blocking(&capture)?, capture.format)?;
+        Ok([pixels[0], pixels[1], pixels[2], pixels[3]])
+    }
+
+    /// uploads `view_projection` into one of the camera buffers, it's used from the next
+    /// submission on
+    fn write_camera(&self, buffer: &wgpu::Buffer, view_projection: Mat4) {
+        let uniform = CameraUniform::from(view_projection);
+        self.queue
+            .write_buffer(buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// records the scene into `view`, which has to match the surface size and format. `timed`
+    /// passes are measured by the gpu timer, if there is one
+    fn encode_scene(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        timed: bool,
+    ) {
+        // taken out while the scene pass borrows all of `self` mutably
+        match self.offscreen.take() {
+            Some(offscreen) => {
+                self.encode_scene_pass(encoder, offscreen.view(), timed);
+                let (origin, size) = self.viewport();
+                offscreen.encode(
+                    encoder,
+                    view,
+                    self.effect,
+                    [
+                        origin.x as f32,
+                        origin.y as f32,
+                        size.width as f32,
+                        size.height as f32,
+                    ],
+                );
+                self.offscreen = Some(offscreen);
+            }
+            None => self.encode_scene_pass(encoder, view, timed),
+        }
+    }
+
+    /// the render pass borrows `encoder` until it's dropped at the end of this function
+    fn encode_scene_pass(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        timed: bool,
+    ) {
+        self.scene.encode(encoder);
+
+        // a pass of its own before the scene's, which then draws on top of it
+        let load = match (self.shader_error.as_ref(), self.background.as_ref()) {
+            (Some(_), _) => wgpu::LoadOp::Clear(wgpu::Color::RED),
+            (None, Some(background)) => {
+                background.encode(
+                    encoder,
+                    self.msaa_view.as_ref().unwrap_or(view),
+                    &self.bind_group,
+                );
+                wgpu::LoadOp::Load
+            }
+            (None, None) => wgpu::LoadOp::Clear(self.clear_colors[self.clear_color]),
+        };
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("scene pass"),
+            color_attachments: &[
+                Some(color_attachment(view, self.msaa_view.as_ref(), load)),
+                Some(color_attachment(
+                    &self.debug_view,
+                    self.debug_msaa_view.as_ref(),
+                    wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                )),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+            }),
+            timestamp_writes: self
+                .gpu_timer
+                .as_ref()
+                .filter(|_| timed)
+                .and_then(GpuTimer::timestamp_writes),
+            occlusion_query_set: None,
+        });
+        if self.shader_error.is_some() {
+            return;
+        }
+
+        // the rect may not reach outside of the attachments, which are the scene's size
+        let size = self.scene_size();
+        if self.scissor < 1.0 {
+            let width = ((size.width as f32 * self.scissor).round() as u32).clamp(1, size.width);
+            let height = ((size.height as f32 * self.scissor).round() as u32).clamp(1, size.height);
+            rpass.set_scissor_rect(
+                (size.width - width) / 2,
+                (size.height - height) / 2,
+                width,
+                height,
+            );
+        }
+        let [left, right] = self.split_viewports();
+        if self.split_screen.is_some() {
+            let [x, y, width, height] = left;
+            rpass.set_viewport(x, y, width, height, 0.0, 1.0);
+        }
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        if let Some(skybox) = self.skybox.as_ref() {
+            skybox.render(&mut rpass);
+        }
+        self.scene.render(&mut rpass);
+        if let Some(split_screen) = self.split_screen.as_ref() {
+            // the same pipelines and draws, only the viewport and the camera change
+            let [x, y, width, height] = right;
+            rpass.set_viewport(x, y, width, height, 0.0, 1.0);
+            rpass.set_bind_group(0, &split_screen.bind_group, &[]);
+            if let Some(skybox) = self.skybox.as_ref() {
+                skybox.render(&mut rpass);
+            }
+            self.scene.render(&mut rpass);
+        }
+        if let Some(spawned) = self.spawned.as_ref() {
+            // clicked into the whole scene, not one of its halves
+            if self.split_screen.is_some() {
+                rpass.set_viewport(0.0, 0.0, size.width as f32, size.height as f32, 0.0, 1.0);
+            }
+            spawned.render(&mut rpass);
+        }
+        // wgpu's gl backend resolves the multisampled attachments with the last scissor rect,
+        // which would leave everything outside of it out of the resolved frame
+        if self.scissor < 1.0 {
+            rpass.set_scissor_rect(0, 0, size.width, size.height);
+        }
+    }
+
+    /// renders the scene a second time into a copyable texture and copies that into a buffer,
+    /// the swapchain texture itself can't be read back. only `pixel` is copied if given
+    fn encode_capture(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        pixel: Option<[u32; 2]>,
+    ) -> PendingCapture {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("capture texture"),
+            size: wgpu::Extent3d {
+                width: self.config.width,
+                height: self.config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        self.encode_scene(
+            encoder,
+            &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            false,
+        );
+
+        let (origin, width, height) = match pixel {
+            Some(pixel) => (pixel, 1, 1),
+            None => ([0, 0], self.config.width, self.config.height),
+        };
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        // even a single pixel takes up a whole aligned row. an hdr surface has 8 bytes per pixel
+        let bytes_per_row = width * texel_size(self.config.format);
+        let padded_bytes_per_row =
+            bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture readback"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                origin: wgpu::Origin3d {
+                    x: origin[0],
+                    y: origin[1],
+                    z: 0,
+                },
+                ..texture.as_image_copy()
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+
+        PendingCapture {
+            buffer,
+            format: self.config.format,
+            origin,
+            width,
+            height,
+            padded_bytes_per_row,
+            mapped: None,
+        }
+    }
+
+    /// checks on the pending captures without blocking, the png is encoded on another thread
+    fn poll_capture(&mut self) {
+        if self.pending_capture.is_none() && self.pending_pick.is_none() {
+            return;
+        }
+
+        self.device.poll(wgpu::Maintain::Poll);
+        if let Some(capture) = PendingCapture::take_mapped(&mut self.pending_capture) {
+            let pixels = capture.take_pixels();
+            self.screenshot_writers
+                .retain(|writer| !writer.is_finished());
+            self.screenshot_writers.push(std::thread::spawn(move || {
+                match save_png(
+                    Path::new(SCREENSHOT_PATH),
+                    pixels,
+                    capture.width,
+                    capture.height,
+                    capture.format,
+                ) {
+                    Ok(()) => log::info!("saved screenshot to {SCREENSHOT_PATH}"),
+                    Err(err) => log::error!("failed to save screenshot: {err}"),
+                }
+            }));
+        }
+
+        if let Some(pick) = PendingCapture::take_mapped(&mut self.pending_pick) {
+            let [x, y] = pick.origin;
+            match to_rgba(pick.take_pixels(), pick.format) {
+                Ok(pixel) => log::info!(
+                    "the pixel at {x}x{y} is #{:02x}{:02x}{:02x}{:02x}",
+                    pixel[0],
+                    pixel[1],
+                    pixel[2],
+                    pixel[3],
+                ),
+                Err(err) => log::error!("failed to pick the pixel at {x}x{y}: {err}"),
+            }
+        }
+    }
+
+    /// blocks until the copy recorded by `encode_capture` is done, it has to be submitted already
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_capture_blocking(&self, capture: &PendingCapture) -> anyhow::Result<Vec<u8>> {
+        let (sender, receiver) = mpsc::channel();
+        capture
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.device.poll(wgpu::Maintain::Wait);
+        // the callback has run once the wait returns, unless the device was lost on the way
+        receiver
+            .recv_timeout(READBACK_TIMEOUT)
+            .map_err(|_| anyhow::anyhow!("the frame wasn't read back in {READBACK_TIMEOUT:?}"))??;
+        // a frame that failed to render isn't worth saving
+        self.uncaptured_error()?;
+        Ok(capture.take_pixels())
+    }
+
+    /// `dx` and `dy` are the cursor movement in physical pixels
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        self.camera.orbit(dx, dy);
+    }
+
+    /// `dx` and `dy` are raw mouse movement, see `OrbitCamera::look`
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        self.camera.look(dx, dy);
+    }
+
+    /// see `OrbitCamera::zoom`
+    pub fn zoom(&mut self, lines: f32) {
+        self.camera.zoom(lines);
+    }
+
+    /// moves the camera, see `OrbitCamera::fly`
+    pub fn fly(&mut self, direction: Vec2, dt: f32) {
+        self.camera.fly(direction, dt);
+    }
+
+    /// the next rendered frame is also saved to `SCREENSHOT_PATH`
+    pub fn request_screenshot(&mut self) {
+        // there's no file system to save it to
+        if cfg!(target_arch = "wasm32") {
+            log::warn!("screenshots aren't supported on the web");
+            return;
+        }
+        self.screenshot_requested = true;
+    }
+
+    /// the color of the pixel at `position` in the next rendered frame is logged, `position` is
+    /// in physical pixels from the top left corner of the window
+    pub fn request_pick(&mut self, position: PhysicalPosition<f64>) {
+        // the cursor can be reported just outside of the window while a button is held
+        self.pick_requested = Some([
+            (position.x.max(0.0) as u32).min(self.config.width - 1),
+            (position.y.max(0.0) as u32).min(self.config.height - 1),
+        ]);
+    }
+
+    /// `position` is in physical pixels from the top left corner of the window
+    pub fn set_cursor(&mut self, position: PhysicalPosition<f64>) {
+        // relative to the letterboxed scene, which the shaders' coordinates are in
+        let (origin, size) = self.viewport();
+        self.mouse = [
+            (position.x as f32 - origin.x as f32) / size.width.max(1) as f32,
+            (position.y as f32 - origin.y as f32) / size.height.max(1) as f32,
+        ];
+    }
+
+    pub fn set_effect(&mut self, effect: Effect) {
+        if effect != Effect::Passthrough {
+            self.create_offscreen();
+        }
+        log::info!("post-processing with {effect:?}");
+        self.effect = effect;
+    }
+
+    /// raises the gamma by `step`, or lowers it if negative, within `Grading::GAMMA_RANGE`
+    pub fn adjust_gamma(&mut self, step: f32) {
+        self.set_grading(Grading::new(
+            self.grading.gamma + step,
+            self.grading.exposure,
+        ));
+    }
+
+    /// like `adjust_gamma`, within `Grading::EXPOSURE_RANGE`
+    pub fn adjust_exposure(&mut self, step: f32) {
+        self.set_grading(Grading::new(
+            self.grading.gamma,
+            self.grading.exposure + step,
+        ));
+    }
+
+    fn set_grading(&mut self, grading: Grading) {
+        self.create_offscreen();
+        if let Some(offscreen) = self.offscreen.as_ref() {
+            offscreen.set_grading(&self.queue, grading);
+        }
+        log::info!(
+            "gamma {:.1}, exposure {:.1}",
+            grading.gamma,
+            grading.exposure
+        );
+        self.grading = grading;
+    }
+
+    /// for the post-processing that needs it, if the scene isn't rendered offscreen already
+    fn create_offscreen(&mut self) {
+        // letterboxing always renders offscreen, so this is the target's size
+        if self.offscreen.is_none() {
+            self.offscreen = Some(Offscreen::new(
+                &self.device,
+                self.scene_format,
+                self.config.format,
+                self.config.width,
+                self.config.height,
+                &self.debug_view,
+                self.grading,
+            ));
+        }
+    }
+
+    /// grows the drawn part of the scene by `step` of its size, or shrinks it if negative
+    pub fn resize_scissor(&mut self, step: f32) {
+        self.scissor = (self.scissor + step).clamp(0.1, 1.0);
+        log::info!("drawing {:.0}% of the scene", self.scissor * 100.0);
+    }
+
+    /// whether clicking spawns triangles, see `spawn`
+    pub fn spawning(&self) -> bool {
+        self.spawned.is_some()
+    }
+
+    /// adds a triangle centered on `position`, in physical pixels from the top left corner of
+    /// the window
+    pub fn spawn(&mut self, position: PhysicalPosition<f64>) {
+        let (origin, size) = self.viewport();
+        let Some(spawned) = self.spawned.as_mut() else {
+            return;
+        };
+        let x = (position.x as f32 - origin.x as f32) / size.width.max(1) as f32;
+        let y = (position.y as f32 - origin.y as f32) / size.height.max(1) as f32;
+        spawned.push(
+            &self.device,
+            &self.queue,
+            Vec2::new(x * 2.0 - 1.0, 1.0 - y * 2.0),
+            size.width as f32 / size.height.max(1) as f32,
+        );
+    }
+
+    /// removes the triangle spawned last
+    pub fn undo_spawn(&mut self) {
+        if let Some(spawned) = self.spawned.as_mut() {
+            spawned.pop();
+        }
+    }
+
+    /// writes the spawned triangles to `SCENE_PATH`, failing only logs
+    pub fn save_spawned(&self) {
+        if let Some(spawned) = self.spawned.as_ref() {
+            if let Err(err) = spawned.save() {
+                log::error!("{err:#}");
+            }
+        }
+    }
+
+    /// replaces the spawned triangles with the ones in `SCENE_PATH`, failing only logs
+    pub fn load_spawned(&mut self) {
+        if let Some(spawned) = self.spawned.as_mut() {
+            if let Err(err) = spawned.load(&self.device, &self.queue) {
+                log::error!("{err:#}");
+            }
+        }
+    }
+
+    /// the framerate the hud shows
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = Some(fps);
+    }
+
+    pub fn toggle_panel(&mut self) {
+        self.panel.toggle();
+    }
+
+    fn panel_values(&self) -> PanelValues {
+        let color = self.clear_colors[self.clear_color];
+        PanelValues {
+            clear_color: [color.r as f32, color.g as f32, color.b as f32],
+            speed: self.speed,
+        }
+    }
+
+    /// hands `event` to the debug panel first, true if it used it and the app shouldn't
+    pub fn panel_event(&mut self, event: &WindowEvent) -> bool {
+        let mut values = self.panel_values();
+        let size = PhysicalSize::new(self.config.width, self.config.height);
+        let used = self
+            .panel
+            .event(event, size, self.overlay_scale(), &mut values);
+        if values != self.panel_values() {
+            let [r, g, b] = values.clear_color.map(f64::from);
+            self.clear_colors[self.clear_color] = wgpu::Color {
+                r,
+                g,
+                b,
+                ..self.clear_colors[self.clear_color]
+            };
+            self.speed = values.speed;
+        }
+        used
+    }
+
+    pub fn next_clear_color(&mut self) {
+        self.clear_color = (self.clear_color + 1) % self.clear_colors.len();
+    }
+
+    /// switches between fifo and the first of mailbox or immediate the surface supports
+    pub fn toggle_vsync(&mut self) {
+        let vsync = matches!(
+            self.config.present_mode,
+            wgpu::PresentMode::Fifo | wgpu::PresentMode::FifoRelaxed
+        );
+        let candidates: &[wgpu::PresentMode] = match vsync {
+            true => &[wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate],
+            false => &[wgpu::PresentMode::Fifo],
+        };
+        let Some(present_mode) = candidates
+            .iter()
+            .find(|mode| self.present_modes.contains(mode))
+        else {
+            log::warn!("the surface only supports {:?}", self.present_modes);
+            return;
+        };
+
+        self.config.present_mode = *present_mode;
+        self.reconfigure();
+        log::info!("using present mode {present_mode:?}");
+    }
+
+    pub fn reconfigure(&self) {
+        if let Some(surface) = self.surface.as_ref() {
+            surface.configure(&self.device, &self.config);
+        }
+    }
+
+    /// the window moved to a monitor with a different scale, the size changes separately
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor as f32;
+    }
+
+    /// the depth buffer has to match the color targets' size, so it's recreated with them
+    fn create_depth_view(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("depth texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// multisampled color target that gets resolved into the swapchain texture,
+    /// `None` when rendering with a single sample
+    fn create_msaa_view(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Option<wgpu::TextureView> {
+        if sample_count == 1 {
+            return None;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("multisampled texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
+    /// sampled by `Offscreen` after the scene is rendered into it
+    fn create_debug_view(device: &wgpu::Device, size: PhysicalSize<u32>) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("debug texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEBUG_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+}
+
+impl Drop for Gfx<'_> {
+    /// waits for the screenshot that's still being read back and for the ones being written,
+    /// so closing the window right after F12 still saves it
+    fn drop(&mut self) {
+        if self.pending_capture.is_some() {
+            self.device.poll(wgpu::Maintain::Wait);
+            self.poll_capture();
+        }
+        for writer in self.screenshot_writers.drain(..) {
+            if writer.join().is_err() {
+                log::error!("the thread saving a screenshot panicked");
+            }
+        }
+    }
+}
+
+/// the largest rectangle with `aspect` centered in `size`, as its top left corner and size. all
+/// of `size` without an aspect
+fn letterbox(
+    size: PhysicalSize<u32>,
+    aspect: Option<f32>,
+) -> (PhysicalPosition<u32>, PhysicalSize<u32>) {
+    let Some(aspect) = aspect else {
+        return (PhysicalPosition::new(0, 0), size);
+    };
+    // bars on the left and right if the target is wider, at the top and bottom if it's taller
+    let width = ((size.height as f32 * aspect).round() as u32).clamp(1, size.width);
+    let height = ((size.width as f32 / aspect).round() as u32).clamp(1, size.height);
+    (
+        PhysicalPosition::new((size.width - width) / 2, (size.height - height) / 2),
+        PhysicalSize::new(width, height),
+    )
+}
+
+/// `load`s `view`, or `msaa_view` with multisampling and resolves it into `view`
+fn color_attachment<'a>(
+    view: &'a wgpu::TextureView,
+    msaa_view: Option<&'a wgpu::TextureView>,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPassColorAttachment<'a> {
+    match msaa_view {
+        Some(msaa_view) => wgpu::RenderPassColorAttachment {
+            view: msaa_view,
+            resolve_target: Some(view),
+            ops: wgpu::Operations {
+                load,
+                // only the resolved image is needed afterwards
+                store: wgpu::StoreOp::Discard,
+            },
+        },
+        None => wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        },
+    }
+}
+
+/// bytes per pixel of a surface or capture texture, none of them is compressed
+fn texel_size(format: wgpu::TextureFormat) -> u32 {
+    format
+        .block_copy_size(None)
+        .expect("color formats have a single aspect")
+}
+
+/// tightly packed `pixels` in `format` as 8 bit rgba. the 8 bit formats only have their channels
+/// swapped, `HDR_FORMAT`'s linear colors are clamped to [0, 1] like a regular display would
+/// show them and sRGB encoded
+fn to_rgba(mut pixels: Vec<u8>, format: wgpu::TextureFormat) -> anyhow::Result<Vec<u8>> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {}
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        HDR_FORMAT => {
+            pixels = pixels
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(i, half)| {
+                    let value = f16_to_f32(u16::from_le_bytes([half[0], half[1]]));
+                    // alpha isn't a color, it stays linear
+                    let value = match i % 4 {
+                        3 => value,
+                        _ if value <= 0.0031308 => value * 12.92,
+                        _ => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+                    };
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+                .collect()
+        }
+        _ => Err(anyhow::anyhow!("can't convert {format:?} to rgba"))?,
+    }
+    Ok(pixels)
+}
+
+/// an ieee 754 half precision float, what `HDR_FORMAT` stores each channel as
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let mantissa = f32::from(half & 0x3ff);
+    sign * match exponent {
+        // subnormal, no implicit leading 1
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// writes tightly packed pixels in `format` to an 8 bit png at `path`
+fn save_png(
+    path: &Path,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> anyhow::Result<()> {
+    let pixels = to_rgba(pixels, format)?;
+
+    image::save_buffer(
+        path,
+        &pixels,
+        width,
+        height,
+        image::ExtendedColorType::Rgba8,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_half_floats() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x3555), 0.33325195);
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn converts_hdr_pixels_to_srgb_rgba() {
+        // linear 0.5, 2.0 (clamped), -1.0 (clamped) and an alpha of 0.5 that isn't encoded
+        let pixel = [0x3800u16, 0x4000, 0xbc00, 0x3800]
+            .iter()
+            .flat_map(|half| half.to_le_bytes())
+            .collect();
+
+        assert_eq!(to_rgba(pixel, HDR_FORMAT).unwrap(), [188, 255, 0, 128]);
+    }
+
+    #[test]
+    fn swaps_bgra_pixels() {
+        let pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+
+        assert_eq!(
+            to_rgba(pixels.clone(), wgpu::TextureFormat::Bgra8UnormSrgb).unwrap(),
+            [3, 2, 1, 4, 7, 6, 5, 8]
+        );
+        assert_eq!(
+            to_rgba(pixels.clone(), wgpu::TextureFormat::Rgba8Unorm).unwrap(),
+            pixels
+        );
+        assert!(to_rgba(pixels, wgpu::TextureFormat::R8Unorm).is_err());
+    }
+}
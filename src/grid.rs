@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+
+use crate::{
+    gfx::DEPTH_FORMAT,
+    scene::{Scene, SceneContext},
+};
+
+/// the grid's shader, used by `--watch`
+pub const SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/grid.wgsl");
+
+const VERTEX_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+};
+
+/// lines one pixel wide every `spacing` pixels, across the whole scene. they're rebuilt
+/// whenever it's resized, so they stay that wide and that far apart at any size
+pub struct GridScene {
+    spacing: u32,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+}
+
+impl GridScene {
+    /// the scene has to be drawn with `pixel_space`
+    pub fn new(context: &SceneContext, spacing: u32) -> Self {
+        let device = context.device;
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("grid pipeline layout"),
+            bind_group_layouts: &[context.frame_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = Self::create_pipeline(context, &pipeline_layout, include_str!("grid.wgsl"));
+        let (vertex_buffer, vertex_count) = Self::create_lines(device, context.size, spacing);
+
+        Self {
+            spacing,
+            pipeline_layout,
+            pipeline,
+            vertex_buffer,
+            vertex_count,
+        }
+    }
+
+    /// pixel `i` covers `i..i + 1`, a line through its center lights up exactly that column or
+    /// row. one along the edge would be split between two and blurred into both
+    fn create_lines(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        spacing: u32,
+    ) -> (wgpu::Buffer, u32) {
+        let (width, height) = (size.width as f32, size.height as f32);
+        let columns = (0..size.width).step_by(spacing as usize).map(|x| {
+            let x = x as f32 + 0.5;
+            [[x, 0.0], [x, height]]
+        });
+        let rows = (0..size.height).step_by(spacing as usize).map(|y| {
+            let y = y as f32 + 0.5;
+            [[0.0, y], [width, y]]
+        });
+        let vertices = columns.chain(rows).flatten().collect::<Vec<[f32; 2]>>();
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("grid vertices"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        (buffer, vertices.len() as u32)
+    }
+
+    fn create_pipeline(
+        context: &SceneContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let device = context.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("grid shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("grid pipeline"),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[VERTEX_LAYOUT],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: context.format,
+                        blend: context.blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(context.debug_format.into()),
+                ],
+            }),
+            // always pairs of vertices, whatever --topology says
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: context.blend.is_none(),
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: context.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+impl Scene for GridScene {
+    fn update(&mut self, _dt: f32) {}
+
+    fn render(&mut self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        rpass.draw(0..self.vertex_count, 0..1);
+    }
+
+    fn resize(&mut self, context: &SceneContext) {
+        (self.vertex_buffer, self.vertex_count) =
+            Self::create_lines(context.device, context.size, self.spacing);
+    }
+
+    fn reload_shader(&mut self, context: &SceneContext, source: &str) -> anyhow::Result<()> {
+        self.pipeline = Self::create_pipeline(context, &self.pipeline_layout, source);
+        Ok(())
+    }
+}
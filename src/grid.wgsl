@@ -0,0 +1,36 @@
+// matches `Globals` in shader.wgsl
+struct Globals {
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    time: f32,
+    scale_factor: f32,
+    // alpha of the lines, 1 unless blending them
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+// `camera::pixel_projection`, the grid is always drawn in pixels
+@group(0) @binding(1)
+var<uniform> view_projection: mat4x4<f32>;
+
+const LINE_COLOR: vec3<f32> = vec3<f32>(0.8, 0.8, 0.8);
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return view_projection * vec4<f32>(position, 0.0, 1.0);
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) debug: vec4<f32>,
+}
+
+@fragment
+fn fs_main() -> FragmentOutput {
+    var out: FragmentOutput;
+    out.color = vec4<f32>(LINE_COLOR, globals.opacity);
+    out.debug = vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    return out;
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowAttributes, WindowId},
+};
+
+use crate::gfx::{Gfx, GfxOptions};
+
+/// prints everything the adapter `options` pick supports, for `--info`. the surface's
+/// capabilities need a window, which is never shown and closes right away. without a display
+/// only the adapter's are printed
+pub fn print(options: &GfxOptions) -> anyhow::Result<()> {
+    let event_loop = match EventLoop::new() {
+        Ok(event_loop) => event_loop,
+        Err(err) => {
+            log::warn!("no surface capabilities without an event loop: {err}");
+            return print_capabilities(options, None);
+        }
+    };
+    let mut app = InfoApp {
+        options,
+        result: None,
+    };
+    event_loop.run_app(&mut app)?;
+    app.result
+        .unwrap_or_else(|| Err(anyhow::anyhow!("the event loop exited before it started")))
+}
+
+/// opens the hidden window once the event loop is running, which winit requires
+struct InfoApp<'a> {
+    options: &'a GfxOptions<'a>,
+    result: Option<anyhow::Result<()>>,
+}
+
+impl ApplicationHandler for InfoApp<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.result.is_some() {
+            return;
+        }
+        let window = event_loop.create_window(
+            WindowAttributes::default()
+                .with_title("info")
+                .with_visible(false),
+        );
+        self.result = Some(match window {
+            Ok(window) => print_capabilities(self.options, Some(Arc::new(window))),
+            Err(err) => Err(err.into()),
+        });
+        event_loop.exit();
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+}
+
+fn print_capabilities(options: &GfxOptions, window: Option<Arc<Window>>) -> anyhow::Result<()> {
+    let instance = Gfx::create_instance(options.backends);
+    let surface = window
+        .map(|window| instance.create_surface(window))
+        .transpose()?;
+    let adapter = pollster::block_on(Gfx::request_adapter(&instance, surface.as_ref(), options))?;
+
+    println!("adapter: {:#?}", adapter.get_info());
+    println!("features:");
+    for (name, _) in adapter.features().iter_names() {
+        println!("    {name}");
+    }
+    println!("limits: {:#?}", adapter.limits());
+    println!("downlevel: {:#?}", adapter.get_downlevel_capabilities());
+
+    match surface {
+        Some(surface) => {
+            let capabilities = surface.get_capabilities(&adapter);
+            println!("surface formats: {:#?}", capabilities.formats);
+            println!("surface present modes: {:#?}", capabilities.present_modes);
+            println!("surface alpha modes: {:#?}", capabilities.alpha_modes);
+            println!("surface usages: {:?}", capabilities.usages);
+        }
+        None => println!("surface: none, there's no display"),
+    }
+    Ok(())
+}
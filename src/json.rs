@@ -0,0 +1,323 @@
+use std::fmt;
+
+/// just enough json for the scene file of the spawned triangles, serde_json isn't available to
+/// this build: objects, arrays and numbers. objects keep their keys in order, which can't
+/// contain escapes, numbers are `f64` like in javascript
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// a whole document, anything but whitespace after the value is an error
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            Err(parser.error("trailing characters"))?;
+        }
+        Ok(value)
+    }
+
+    /// the value of `key` if this is an object that has it
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+impl From<&[f32]> for Value {
+    fn from(numbers: &[f32]) -> Self {
+        Self::Array(
+            numbers
+                .iter()
+                // through the shortest text that reads back as the same f32, `0.05` would
+                // otherwise be written as `0.05000000074505806`
+                .map(|&number| Self::Number(number.to_string().parse().unwrap()))
+                .collect(),
+        )
+    }
+}
+
+/// on one line, with a space after every `,` and `:`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // rust never formats a finite float with an exponent, json has no infinity or nan
+            Self::Number(number) => {
+                assert!(number.is_finite(), "{number} can't be written as json");
+                write!(f, "{number}")
+            }
+            Self::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Object(members) => {
+                f.write_str("{")?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "\"{name}\": {value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    /// in bytes, always on a char boundary
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> anyhow::Error {
+        // 1-based like an editor's, the column counts chars
+        let before = &self.source[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        anyhow::anyhow!("{message} at line {line}, column {column}")
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> anyhow::Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            Err(self.error(&format!("expected `{}`", byte as char)))?;
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected an object, an array or a number")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn object(&mut self) -> anyhow::Result<Value> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                Err(self.error("expected a key"))?;
+            }
+            let name = self.key()?;
+            self.expect(b':')?;
+            members.push((name, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => Err(self.error("expected `,` or `}`"))?,
+            }
+        }
+    }
+
+    fn array(&mut self) -> anyhow::Result<Value> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => Err(self.error("expected `,` or `]`"))?,
+            }
+        }
+    }
+
+    /// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`, rust's own parsing accepts more
+    fn number(&mut self) -> anyhow::Result<Value> {
+        let start = self.position;
+        self.eat(b'-');
+        if !self.eat(b'0') && self.digits() == 0 {
+            Err(self.error("invalid number"))?;
+        }
+        if self.eat(b'.') && self.digits() == 0 {
+            Err(self.error("expected a digit after `.`"))?;
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            let _ = self.eat(b'+') || self.eat(b'-');
+            if self.digits() == 0 {
+                Err(self.error("expected a digit in the exponent"))?;
+            }
+        }
+        if let Some(b'0'..=b'9') = self.peek() {
+            Err(self.error("leading zero"))?;
+        }
+        let number = self.source[start..self.position].parse().unwrap();
+        Ok(Value::Number(number))
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    /// how many were skipped
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    /// starting at its opening quote, escapes aren't needed for the scene's keys
+    fn key(&mut self) -> anyhow::Result<String> {
+        self.position += 1;
+        let rest = &self.source[self.position..];
+        let Some(length) = rest.find(['"', '\\']) else {
+            Err(self.error("unterminated string"))?
+        };
+        if rest.as_bytes()[length] == b'\\' {
+            self.position += length;
+            Err(self.error("escapes aren't supported"))?;
+        }
+        if rest[..length].chars().any(char::is_control) {
+            Err(self.error("control character in a string"))?;
+        }
+        self.position += length + 1;
+        Ok(rest[..length].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn parses_objects_arrays_and_numbers() {
+        let value = Value::parse(r#" {"a": [1, -2.5, 3e2], "b": {"c": []}} "#).unwrap();
+        assert_eq!(
+            value,
+            Value::Object(vec![
+                (
+                    "a".into(),
+                    Value::Array(vec![
+                        Value::Number(1.0),
+                        Value::Number(-2.5),
+                        Value::Number(300.0)
+                    ])
+                ),
+                (
+                    "b".into(),
+                    Value::Object(vec![("c".into(), Value::Array(vec![]))])
+                ),
+            ])
+        );
+        assert_eq!(
+            value.get("b").and_then(|b| b.get("c")),
+            Some(&Value::Array(vec![]))
+        );
+        assert_eq!(value.get("g"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for source in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "{a: 1}",
+            "{\"a\\n\": 1}",
+            "{\"open: 1}",
+            "01",
+            "1.",
+            ".5",
+            "+1",
+            "null",
+            "\"text\"",
+            "1 2",
+        ] {
+            assert!(Value::parse(source).is_err(), "{source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_where_it_failed() {
+        let error = Value::parse("{\n  \"a\": x\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected an object, an array or a number at line 2, column 8"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let source = r#"{"a": [0.05, -1, 2.5], "b": {}}"#;
+        let value = Value::parse(source).unwrap();
+        assert_eq!(value.to_string(), source);
+        assert_eq!(Value::parse(&value.to_string()).unwrap(), value);
+    }
+}
@@ -1,228 +1,862 @@
-use std::{
-    borrow::Cow,
-    sync::{mpsc, Arc},
-};
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
 
+use clap::Parser;
 use winit::{
-    application::ApplicationHandler,
     dpi::PhysicalSize,
-    event::WindowEvent,
-    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
-    window::{Window, WindowAttributes, WindowId},
+    event_loop::{ControlFlow, EventLoop},
 };
 
-struct EventHandler {
-    event_sender: mpsc::Sender<Event>,
+use app::App;
+use config::Config;
+use gfx::{GfxBuilder, GfxOptions, DEFAULT_SAMPLE_COUNT, DEFAULT_SIZE};
+use grid::GridScene;
+use mesh::MeshScene;
+use offscreen::{Effect, Grading};
+use quad::QuadScene;
+use scene::{Scene, SceneContext};
+use triangle::TriangleScene;
+// headless rendering and listing adapters are native only
+#[cfg(not(target_arch = "wasm32"))]
+use {gfx::Gfx, std::path::Path};
+
+mod app;
+mod background;
+mod camera;
+mod config;
+#[cfg(target_os = "linux")]
+mod gamepad;
+mod gfx;
+mod grid;
+#[cfg(not(target_arch = "wasm32"))]
+mod info;
+mod json;
+mod mesh;
+mod obj;
+mod offscreen;
+mod panel;
+mod pattern;
+mod placement;
+mod quad;
+mod scene;
+mod skybox;
+mod spawn;
+mod text;
+mod timing;
+mod triangle;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+#[derive(Parser, Debug, Clone)]
+struct Args {
+    /// how frames are presented, falls back to fifo if the surface doesn't support it
+    /// [default: the config's, or fifo]
+    #[arg(long, value_enum)]
+    present_mode: Option<PresentMode>,
+
+    /// toml file with the window size, clear color, present mode, sample count and backend
+    /// [default: config.toml, if it exists]
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// render a single frame without opening a window and save it as a png to this path
+    #[arg(long, value_name = "PATH")]
+    headless: Option<PathBuf>,
+
+    /// render this many frames without opening a window and save them into --out-dir, each
+    /// one 1/60 s of animation after the previous one
+    #[arg(
+        long,
+        value_name = "FRAMES",
+        requires = "out_dir",
+        conflicts_with_all = ["headless", "bench"],
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    capture_frames: Option<u32>,
+
+    /// where --capture-frames writes 000001.png, 000002.png and so on, created if it's missing
+    #[arg(long, value_name = "DIR", requires = "capture_frames")]
+    out_dir: Option<PathBuf>,
+
+    /// check that the middle of the --headless frame of `--scene triangle` is the average of
+    /// its corners' colors, and fail if it isn't
+    #[arg(long, requires = "headless")]
+    check_interpolation: bool,
+
+    /// use the first adapter whose name contains this
+    #[arg(long, value_name = "SUBSTRING")]
+    adapter: Option<String>,
+
+    /// record an api trace into this directory, to replay it with wgpu's player. wgpu 23 has
+    /// tracing disabled (gfx-rs/wgpu#5974) and only logs an error until it's back
+    #[arg(long, value_name = "DIR", conflicts_with = "windows")]
+    trace: Option<PathBuf>,
+
+    /// prefer the integrated (low) or the discrete (high) gpu, unless --adapter picks one
+    #[arg(long, value_enum)]
+    power: Option<PowerPreference>,
+
+    /// use wgpu's software fallback adapter, for telling driver bugs apart from our own
+    #[arg(long, conflicts_with = "adapter")]
+    fallback: bool,
+
+    /// print all available adapters and exit
+    #[arg(long)]
+    list_adapters: bool,
+
+    /// print the info, features and limits of the adapter the other options pick and what its
+    /// surfaces support, then exit
+    #[arg(long)]
+    info: bool,
+
+    /// load the scene's shader from src/ and reload it whenever it changes
+    #[arg(long)]
+    watch: bool,
+
+    /// what to draw
+    #[arg(long, value_enum, default_value_t = SceneKind::Quads)]
+    scene: SceneKind,
+
+    /// pixels from one line of `--scene grid` to the next
+    #[arg(long, value_name = "PIXELS", default_value_t = 32, value_parser = clap::value_parser!(u32).range(2..))]
+    grid_spacing: u32,
+
+    /// draw the obj file at this path instead of a scene
+    #[arg(long, value_name = "PATH", conflicts_with = "scene")]
+    model: Option<PathBuf>,
+
+    /// number of quads to draw, laid out in a grid
+    #[arg(long, default_value_t = 1)]
+    instances: u32,
+
+    /// draw outlines only, if the adapter supports it
+    #[arg(long)]
+    wireframe: bool,
+
+    /// how vertices are put together, points and lines show where the vertices are
+    #[arg(long, value_enum, default_value_t = Topology::TriangleList)]
+    topology: Topology,
+
+    /// which faces aren't drawn [default: back for the cube and models, none for the quads]
+    #[arg(long, value_enum)]
+    cull: Option<Cull>,
+
+    /// the winding of front faces, as seen on screen
+    #[arg(long, value_enum, default_value_t = Winding::Ccw)]
+    winding: Winding,
+
+    /// blend the scene over the clear color with this alpha, in [0, 1]. try it with
+    /// `--scene cube --cull none` to see the back faces through the front ones
+    #[arg(long, value_parser = parse_opacity)]
+    opacity: Option<f32>,
+
+    /// gamma correction of the frame, the colors are raised to 1 / GAMMA. [ and ] adjust it,
+    /// anything but 1 renders offscreen
+    #[arg(long, default_value_t = 1.0, value_parser = parse_gamma)]
+    gamma: f32,
+
+    /// brightness the frame is multiplied with after the gamma. , and . adjust it, anything
+    /// but 1 renders offscreen
+    #[arg(long, default_value_t = 1.0, value_parser = parse_exposure)]
+    exposure: f32,
+
+    /// png to draw onto the quad
+    #[arg(long, value_name = "PATH")]
+    texture: Option<PathBuf>,
+
+    /// draw an animated pattern from a compute shader onto the quads instead of a png
+    #[arg(long, conflicts_with = "texture")]
+    pattern: bool,
+
+    /// draw a solid outline around the quads, masked by the stencil buffer
+    #[arg(long)]
+    outline: bool,
+
+    /// the first clear color, as an sRGB hex color like 1a334d, before the built-in ones that
+    /// clicking cycles through [default: the config's]
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_hex_color)]
+    clear_color: Option<wgpu::Color>,
+
+    /// render to a linear instead of an sRGB swapchain format, for comparison
+    #[arg(long)]
+    no_srgb: bool,
+
+    /// render in a linear floating point format and tone map it, on a surface that can show
+    /// it if there is one
+    #[arg(long)]
+    hdr: bool,
+
+    /// render into a texture first and copy that to the screen in a second pass, should look
+    /// exactly like rendering directly
+    #[arg(long)]
+    offscreen: bool,
+
+    /// render the scene at this fixed resolution and upscale it to the window with nearest
+    /// filtering, for a pixelated look. implies --offscreen
+    #[arg(long, value_name = "WxH", value_parser = parse_resolution)]
+    internal_res: Option<PhysicalSize<u32>>,
+
+    /// keep this aspect ratio, like 16:9, with black bars where the window's differs
+    /// [default: the --internal-res one, stretched to the window without it]
+    #[arg(long, value_name = "W:H", value_parser = parse_aspect)]
+    aspect: Option<f32>,
+
+    /// draw the scene twice side by side in one render pass, the right half seen from another
+    /// angle
+    #[arg(long)]
+    split_screen: bool,
+
+    /// draw a sky-like vertical gradient behind the scene instead of the clear color
+    #[arg(long)]
+    gradient: bool,
+
+    /// draw a cube map behind the scene, from px.png, nx.png, py.png, ny.png, pz.png and
+    /// nz.png in this directory. pz is in front of the camera
+    #[arg(long, value_name = "DIR", conflicts_with = "gradient")]
+    skybox: Option<PathBuf>,
+
+    /// project pixel coordinates from the top left corner instead of looking through the
+    /// orbit camera, `--scene triangle` is then placed in pixels
+    #[arg(long, conflicts_with = "check_interpolation")]
+    pixel_space: bool,
+
+    /// clicking spawns a small triangle at the cursor instead of cycling the clear color,
+    /// backspace removes the last one. ctrl+s saves them to scene.json, ctrl+o loads them back
+    #[arg(long)]
+    spawn: bool,
+
+    /// post-processing applied to the rendered scene, switch with 1 to 4 at runtime
+    #[arg(long, value_enum, default_value_t = Effect::Passthrough)]
+    effect: Effect,
+
+    /// sharpens textures seen at an angle, up to this many samples per pixel. 1 turns it off
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=16))]
+    anisotropy: u16,
+
+    /// samples per pixel for antialiasing, falls back to the highest supported count below it
+    /// [default: the config's, or 4]
+    #[arg(long, value_name = "SAMPLES", value_parser = parse_sample_count)]
+    msaa: Option<u32>,
+
+    /// request the adapter's maximum limits instead of the defaults, for large textures
+    #[arg(long)]
+    max_limits: bool,
+
+    /// request only the limits WebGL2 guarantees instead of the defaults, to check that
+    /// everything fits into them
+    #[arg(long, conflicts_with = "max_limits")]
+    downlevel_limits: bool,
+
+    /// comma separated wgpu features to require on top of the ones used anyway, like
+    /// TEXTURE_COMPRESSION_BC. the adapter has to support all of them
+    #[arg(long, value_name = "FEATURES", value_delimiter = ',', value_parser = parse_feature)]
+    features: Vec<wgpu::Features>,
+
+    /// exit with the first wgpu validation error nothing handled instead of panicking on it
+    #[arg(long)]
+    exit_on_error: bool,
+
+    /// render this many frames as fast as possible, print their cpu frame times and exit.
+    /// presents immediately unless --present-mode says otherwise
+    #[arg(long, value_name = "FRAMES", conflicts_with = "headless")]
+    bench: Option<u32>,
+
+    /// frames at the start of --bench that aren't part of the statistics, the first one
+    /// includes the pipeline warmup
+    #[arg(long, value_name = "FRAMES", default_value_t = 1, requires = "bench")]
+    bench_warmup: u32,
+
+    /// window width in physical pixels, also of the --headless frame [default: the config's,
+    /// or 1280]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    width: Option<u32>,
+
+    /// window height in physical pixels, also of the --headless frame [default: the config's,
+    /// or 720]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    height: Option<u32>,
+
+    /// window title, numbered with more than one window [default: triangle]
+    #[arg(long)]
+    title: Option<String>,
+
+    /// keep rendering while the window doesn't have the focus, instead of pausing to save power
+    #[arg(long)]
+    keep_rendering_unfocused: bool,
+
+    /// number of windows to open, each with its own device and a different clear color
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    windows: u32,
+
+    /// render at most this many frames per second, independent of the present mode. 0 doesn't
+    /// cap the frame rate
+    #[arg(long, value_name = "FPS", default_value_t = 0)]
+    fps_cap: u32,
+
+    /// exit after rendering for this many seconds, counted from the first frame
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    run_for: Option<Duration>,
+
+    /// draw the framerate and frame time into the top left corner of the window
+    #[arg(long)]
+    hud: bool,
 }
 
-enum Event {
-    WindowCreated(Window),
-    WindowClose,
-    RedrawRequested,
+fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    let seconds = seconds.parse::<f64>().map_err(|err| err.to_string())?;
+    Duration::try_from_secs_f64(seconds).map_err(|err| err.to_string())
 }
 
-struct State<'state> {
-    event_receiver: mpsc::Receiver<Event>,
-    window: Option<Arc<Window>>,
-    gfx: Option<Gfx<'state>>,
+fn parse_resolution(resolution: &str) -> Result<PhysicalSize<u32>, String> {
+    let (width, height) = resolution
+        .split_once('x')
+        .ok_or_else(|| format!("{resolution} is not of the form WxH, like 320x180"))?;
+    let parse = |size: &str| match size.parse::<u32>() {
+        Ok(0) => Err("the resolution can't be 0".to_string()),
+        result => result.map_err(|err| format!("{size}: {err}")),
+    };
+    Ok(PhysicalSize::new(parse(width)?, parse(height)?))
 }
 
-struct Gfx<'gfx> {
-    surface: wgpu::Surface<'gfx>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    pipeline: wgpu::RenderPipeline,
+fn parse_aspect(aspect: &str) -> Result<f32, String> {
+    let (width, height) = aspect
+        .split_once(':')
+        .ok_or_else(|| format!("{aspect} is not of the form W:H, like 16:9"))?;
+    let parse = |size: &str| match size.parse::<f32>() {
+        Ok(size) if size > 0.0 && size.is_finite() => Ok(size),
+        Ok(_) => Err(format!("{size} is not a positive number")),
+        Err(err) => Err(format!("{size}: {err}")),
+    };
+    Ok(parse(width)? / parse(height)?)
 }
 
-impl<'gfx> Gfx<'gfx> {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
-        let size = window.as_ref().inner_size();
+/// clear values are linear, the target's sRGB encoding is applied on top of them when it's
+/// written. dividing the hex digits by 255 alone would come out too bright
+fn parse_hex_color(hex: &str) -> Result<wgpu::Color, String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if digits.len() != 6 || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(format!("{hex} is not a hex color of the form RRGGBB"));
+    }
+    let linear = |i: usize| {
+        let srgb = u8::from_str_radix(&digits[i..i + 2], 16).unwrap() as f64 / 255.0;
+        if srgb <= 0.04045 {
+            srgb / 12.92
+        } else {
+            ((srgb + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Ok(wgpu::Color {
+        r: linear(0),
+        g: linear(2),
+        b: linear(4),
+        a: 1.0,
+    })
+}
 
-        let instance = wgpu::Instance::default();
+fn parse_feature(name: &str) -> Result<wgpu::Features, String> {
+    wgpu::Features::from_name(&name.to_ascii_uppercase())
+        .ok_or_else(|| format!("{name} is not a wgpu feature"))
+}
 
-        let surface = instance.create_surface(window.clone())?;
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            })
-            .await
-            .ok_or_else(|| anyhow::anyhow!("no adapter found!"))?;
+fn parse_sample_count(count: &str) -> Result<u32, String> {
+    match count.parse::<u32>().map_err(|err| err.to_string())? {
+        count @ (1 | 2 | 4 | 8) => Ok(count),
+        count => Err(format!("{count} is not one of 1, 2, 4 or 8")),
+    }
+}
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: None,
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(), // maybe source of some trouble
-                    memory_hints: wgpu::MemoryHints::MemoryUsage,
-                },
-                None,
-            )
-            .await?;
-
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-
-        let swapchain_capabilities = surface.get_capabilities(&adapter);
-        let swapchain_format = swapchain_capabilities.formats[0];
-
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                compilation_options: Default::default(),
-                targets: &[Some(swapchain_format.into())],
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
-
-        let config = surface
-            .get_default_config(&adapter, size.width, size.height)
-            .ok_or_else(|| anyhow::anyhow!("failed to create config!"))?;
-        surface.configure(&device, &config);
-
-        Ok(Self {
-            surface,
-            device,
-            queue,
-            pipeline,
-        })
+fn parse_opacity(opacity: &str) -> Result<f32, String> {
+    let opacity = opacity.parse::<f32>().map_err(|err| err.to_string())?;
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(format!("{opacity} is not in [0, 1]"));
+    }
+    Ok(opacity)
+}
+
+fn parse_gamma(gamma: &str) -> Result<f32, String> {
+    parse_in(gamma, Grading::GAMMA_RANGE)
+}
+
+fn parse_exposure(exposure: &str) -> Result<f32, String> {
+    parse_in(exposure, Grading::EXPOSURE_RANGE)
+}
+
+fn parse_in(value: &str, range: RangeInclusive<f32>) -> Result<f32, String> {
+    let value = value.parse::<f32>().map_err(|err| err.to_string())?;
+    if !range.contains(&value) {
+        return Err(format!(
+            "{value} is not in [{}, {}]",
+            range.start(),
+            range.end()
+        ));
     }
+    Ok(value)
 }
 
-impl<'state> State<'state> {
-    pub async fn new(receiver: mpsc::Receiver<Event>) -> State<'state> {
-        Self {
-            event_receiver: receiver,
-            window: None,
-            gfx: None,
-        }
-    }
-
-    pub async fn run(&mut self) -> anyhow::Result<()> {
-        match self.event_receiver.recv()? {
-            Event::WindowCreated(window) => self.window = Some(Arc::new(window)),
-            _ => Err(anyhow::anyhow!("unexpected event"))?,
-        };
-
-        self.gfx = Some(Gfx::new(self.window.clone().unwrap()).await?);
-
-        loop {
-            match self.event_receiver.recv()? {
-                Event::RedrawRequested => {
-                    let gfx = self.gfx.as_ref().unwrap();
-
-                    let frame = gfx.surface.get_current_texture()?;
-                    let view = frame
-                        .texture
-                        .create_view(&wgpu::TextureViewDescriptor::default());
-
-                    let mut encoder = gfx
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
-                    {
-                        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                            label: None,
-                            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                                view: &view,
-                                resolve_target: None,
-                                ops: wgpu::Operations {
-                                    load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
-                                    store: wgpu::StoreOp::Store,
-                                },
-                            })],
-                            depth_stencil_attachment: None,
-                            timestamp_writes: None,
-                            occlusion_query_set: None,
-                        });
-                        rpass.set_pipeline(&gfx.pipeline);
-                        rpass.draw(0..3, 0..1);
-                    }
-
-                    gfx.queue.submit(Some(encoder.finish()));
-                    frame.present();
-                }
-                Event::WindowClose => break,
-                _ => {}
-            }
-        }
-        Ok(())
-    }
-}
-
-impl ApplicationHandler for EventHandler {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let window = event_loop
-            .create_window(
-                WindowAttributes::default()
-                    .with_resizable(false)
-                    .with_inner_size(PhysicalSize::<u32>::from((1280, 720))),
+impl Args {
+    fn create_scene(&self, context: &SceneContext) -> anyhow::Result<Box<dyn Scene>> {
+        Ok(match self.scene_kind() {
+            SceneKind::Quads => Box::new(QuadScene::new(
+                context,
+                self.instances,
+                self.texture.as_deref(),
+                self.pattern,
+                self.outline,
+            )?),
+            SceneKind::Cube => Box::new(MeshScene::cube(context)),
+            SceneKind::Triangle => Box::new(TriangleScene::new(context)),
+            SceneKind::Grid => Box::new(GridScene::new(context, self.grid_spacing)),
+            SceneKind::Model => Box::new(MeshScene::load(
+                context,
+                self.model.as_deref().expect("only --model draws a model"),
+            )?),
+        })
+    }
+
+    fn scene_kind(&self) -> SceneKind {
+        match self.model {
+            Some(_) => SceneKind::Model,
+            None => self.scene,
+        }
+    }
+
+    /// the least time between two frames for `--fps-cap`
+    fn frame_interval(&self) -> Option<Duration> {
+        (self.fps_cap > 0).then(|| Duration::from_secs_f64(1.0 / self.fps_cap as f64))
+    }
+
+    /// flags override the size the window was `saved` at, which overrides the config, which
+    /// overrides `DEFAULT_SIZE`
+    fn window_size(&self, config: &Config, saved: Option<PhysicalSize<u32>>) -> PhysicalSize<u32> {
+        let size = saved.or(config.window_size).unwrap_or(DEFAULT_SIZE);
+        PhysicalSize::new(
+            self.width.unwrap_or(size.width),
+            self.height.unwrap_or(size.height),
+        )
+    }
+
+    /// flags override the config, which overrides the defaults
+    fn gfx_builder<'a>(&'a self, config: &Config) -> GfxBuilder<'a> {
+        let mut builder = GfxBuilder::from(self.gfx_options(config))
+            .msaa(
+                self.msaa
+                    .or(config.sample_count)
+                    .unwrap_or(DEFAULT_SAMPLE_COUNT),
+            )
+            // vsync would only measure the refresh rate
+            .present_mode(
+                self.present_mode
+                    .or(self.bench.map(|_| PresentMode::Immediate))
+                    .or(config.present_mode)
+                    .unwrap_or(PresentMode::Fifo)
+                    .into(),
             )
-            .unwrap();
+            .features(self.features.iter().copied().collect());
+        if let Some(color) = self.clear_color.or(config.clear_color) {
+            builder = builder.clear_color(color);
+        }
+        if self.downlevel_limits {
+            builder = builder.limits(wgpu::Limits::downlevel_webgl2_defaults());
+        }
+        builder
+    }
 
-        self.event_sender
-            .send(Event::WindowCreated(window))
-            .unwrap();
+    /// what picks the adapter and sets up the scene, `gfx_builder` adds the rest. `--info`
+    /// only needs these
+    fn gfx_options<'a>(&'a self, config: &Config) -> GfxOptions<'a> {
+        GfxOptions {
+            adapter_name: self.adapter.as_deref(),
+            trace: self.trace.as_deref(),
+            power_preference: self.power.map(Into::into).unwrap_or_default(),
+            force_fallback_adapter: self.fallback,
+            wireframe: self.wireframe,
+            topology: self.topology.into(),
+            // meshes are usually closed, so their back faces are hidden anyway
+            cull_mode: self
+                .cull
+                .unwrap_or(match self.scene_kind() {
+                    SceneKind::Quads | SceneKind::Triangle | SceneKind::Grid => Cull::None,
+                    SceneKind::Cube | SceneKind::Model => Cull::Back,
+                })
+                .into(),
+            front_face: self.winding.into(),
+            opacity: self.opacity,
+            srgb: !self.no_srgb,
+            hdr: self.hdr,
+            offscreen: self.offscreen,
+            internal_size: self.internal_res,
+            aspect: self.aspect,
+            effect: self.effect,
+            grading: Grading::new(self.gamma, self.exposure),
+            split_screen: self.split_screen,
+            gradient: self.gradient,
+            skybox: self.skybox.as_deref(),
+            spawn: self.spawn,
+            hud: self.hud,
+            pixel_space: self.pixel_space || matches!(self.scene_kind(), SceneKind::Grid),
+            anisotropy: self.anisotropy,
+            max_limits: self.max_limits,
+            exit_on_error: self.exit_on_error,
+            backends: config.backends,
+            ..Default::default()
+        }
     }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum SceneKind {
+    /// the textured quads from --instances and --texture
+    Quads,
+    /// a spinning cube
+    Cube,
+    /// a triangle with a red, a green and a blue corner, blended in between
+    Triangle,
+    /// lines exactly one pixel wide every --grid-spacing pixels, implies --pixel-space
+    Grid,
+    /// the obj file from --model
+    #[value(skip)]
+    Model,
+}
 
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        match event {
-            WindowEvent::CloseRequested => {
-                self.event_sender.send(Event::WindowClose).unwrap();
-                event_loop.exit();
-            }
+impl SceneKind {
+    /// the source of the shader built into the binary, for `--watch`
+    fn shader_path(self) -> &'static str {
+        match self {
+            Self::Quads => quad::SHADER_PATH,
+            Self::Cube | Self::Model => mesh::SHADER_PATH,
+            Self::Triangle => triangle::SHADER_PATH,
+            Self::Grid => grid::SHADER_PATH,
+        }
+    }
+}
 
-            WindowEvent::RedrawRequested => {
-                self.event_sender.send(Event::RedrawRequested).unwrap();
-            }
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Topology {
+    PointList,
+    LineList,
+    LineStrip,
+    TriangleList,
+    TriangleStrip,
+}
 
-            _ => {}
+impl From<Topology> for wgpu::PrimitiveTopology {
+    fn from(topology: Topology) -> Self {
+        match topology {
+            Topology::PointList => wgpu::PrimitiveTopology::PointList,
+            Topology::LineList => wgpu::PrimitiveTopology::LineList,
+            Topology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+            Topology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+            Topology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
         }
     }
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    let event_loop = EventLoop::new()?;
-    event_loop.set_control_flow(ControlFlow::Poll);
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Cull {
+    None,
+    Front,
+    Back,
+}
 
-    let (tx, rx) = mpsc::channel();
+impl From<Cull> for Option<wgpu::Face> {
+    fn from(cull: Cull) -> Self {
+        match cull {
+            Cull::None => None,
+            Cull::Front => Some(wgpu::Face::Front),
+            Cull::Back => Some(wgpu::Face::Back),
+        }
+    }
+}
 
-    let mut app = EventHandler { event_sender: tx };
-    tokio::spawn(async move {
-        let mut state = State::new(rx).await;
-        state.run().await.unwrap();
-    });
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Winding {
+    Ccw,
+    Cw,
+}
 
-    event_loop.run_app(&mut app)?;
+impl From<Winding> for wgpu::FrontFace {
+    fn from(winding: Winding) -> Self {
+        match winding {
+            Winding::Ccw => wgpu::FrontFace::Ccw,
+            Winding::Cw => wgpu::FrontFace::Cw,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum PowerPreference {
+    Low,
+    High,
+}
+
+impl From<PowerPreference> for wgpu::PowerPreference {
+    fn from(preference: PowerPreference) -> Self {
+        match preference {
+            PowerPreference::Low => wgpu::PowerPreference::LowPower,
+            PowerPreference::High => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum PresentMode {
+    Fifo,
+    FifoRelaxed,
+    Mailbox,
+    Immediate,
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn list_adapters(config: &Config) {
+    let instance = Gfx::create_instance(config.backends);
+    for adapter in instance.enumerate_adapters(wgpu::Backends::all()) {
+        let info = adapter.get_info();
+        println!("{} ({:?}, {:?})", info.name, info.backend, info.device_type);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run_headless(path: &Path, args: &Args, config: &Config) -> anyhow::Result<()> {
+    if args.check_interpolation && !matches!(args.scene_kind(), SceneKind::Triangle) {
+        Err(anyhow::anyhow!(
+            "--check-interpolation only works with --scene triangle"
+        ))?;
+    }
+    let mut gfx = pollster::block_on(
+        args.gfx_builder(config)
+            .build_headless(args.window_size(config, None)),
+    )?;
+    let scene = args.create_scene(&gfx.scene_context())?;
+    gfx.set_scene(scene);
+    gfx.update(0.0);
+    gfx.save_frame(path)?;
+    log::info!("saved frame to {}", path.display());
+
+    if args.check_interpolation {
+        let size = args.window_size(config, None);
+        let pixel = gfx.read_pixel([size.width / 2, size.height / 2])?;
+        TriangleScene::check_interpolation(pixel, !args.no_srgb)?;
+    }
+    Ok(())
+}
+
+/// the animation time between two frames of --capture-frames, independent of how long they
+/// take to render
+#[cfg(not(target_arch = "wasm32"))]
+const CAPTURE_FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// every frame is read back before the next one is rendered, so there's only ever one
+/// readback buffer alive
+#[cfg(not(target_arch = "wasm32"))]
+fn run_capture(frames: u32, dir: &Path, args: &Args, config: &Config) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut gfx = pollster::block_on(
+        args.gfx_builder(config)
+            .build_headless(args.window_size(config, None)),
+    )?;
+    let scene = args.create_scene(&gfx.scene_context())?;
+    gfx.set_scene(scene);
+    for frame in 1..=frames {
+        // the first frame shows the scene before it moved, like --headless
+        gfx.update(if frame == 1 { 0.0 } else { CAPTURE_FRAME_TIME });
+        let path = dir.join(format!("{frame:06}.png"));
+        gfx.save_frame(&path)?;
+        log::info!("saved frame {frame}/{frames} to {}", path.display());
+    }
     Ok(())
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+    let config = Config::load(args.config.as_deref())?;
+
+    if args.list_adapters {
+        list_adapters(&config);
+        return Ok(());
+    }
+
+    if args.info {
+        return info::print(&args.gfx_options(&config));
+    }
+
+    if let Some(path) = args.headless.as_deref() {
+        return run_headless(path, &args, &config);
+    }
+
+    if let (Some(frames), Some(dir)) = (args.capture_frames, args.out_dir.as_deref()) {
+        return run_capture(frames, dir, &args, &config);
+    }
+
+    if args.bench.is_some_and(|frames| frames <= args.bench_warmup) {
+        Err(anyhow::anyhow!(
+            "--bench needs more frames than the {} of --bench-warmup",
+            args.bench_warmup
+        ))?;
+    }
+
+    let event_loop = EventLoop::with_user_event().build()?;
+    // redraws are requested after every frame, so the event loop can sleep in between. `Poll`
+    // would spin at 100% even while `present` is blocked on vsync.
+    event_loop.set_control_flow(ControlFlow::Wait);
+
+    let mut app = App::new(args, config, event_loop.create_proxy());
+    event_loop.run_app(&mut app)?;
+    app.finish()
+}
+
+/// built with `cargo build --target wasm32-unknown-unknown`, see `web.rs` for the rest
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use winit::platform::web::EventLoopExtWebSys;
+
+    web::init_logging();
+    // there's no command line in the browser, everything is left at its default
+    let args = Args::parse_from([env!("CARGO_PKG_NAME")]);
+    // nor a config file to read
+    let config = Config::default();
+
+    let event_loop = EventLoop::with_user_event()
+        .build()
+        .expect("failed to create the event loop");
+    event_loop.set_control_flow(ControlFlow::Wait);
+
+    let app = App::new(args, config, event_loop.create_proxy());
+    // returns right away, the browser keeps calling into `app` from its own event loop
+    event_loop.spawn_app(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_seconds() {
+        assert_eq!(parse_seconds("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_seconds("0.25"), Ok(Duration::from_millis(250)));
+        for seconds in ["", "-1", "NaN", "two"] {
+            assert!(parse_seconds(seconds).is_err(), "{seconds}");
+        }
+    }
+
+    #[test]
+    fn parses_opacities() {
+        assert_eq!(parse_opacity("0"), Ok(0.0));
+        assert_eq!(parse_opacity("0.5"), Ok(0.5));
+        assert_eq!(parse_opacity("1"), Ok(1.0));
+        for opacity in ["", "-0.1", "1.5", "NaN", "half"] {
+            assert!(parse_opacity(opacity).is_err(), "{opacity}");
+        }
+    }
+
+    #[test]
+    fn parses_resolutions() {
+        assert_eq!(parse_resolution("320x180"), Ok(PhysicalSize::new(320, 180)));
+        assert_eq!(parse_resolution("1x1"), Ok(PhysicalSize::new(1, 1)));
+    }
+
+    #[test]
+    fn rejects_invalid_resolutions() {
+        for resolution in [
+            "",
+            "320",
+            "320:180",
+            "0x180",
+            "320x0",
+            "-1x1",
+            "320x180x1",
+            "wxh",
+        ] {
+            assert!(parse_resolution(resolution).is_err(), "{resolution}");
+        }
+    }
+
+    #[test]
+    fn parses_aspect_ratios() {
+        assert_eq!(parse_aspect("16:9"), Ok(16.0 / 9.0));
+        assert_eq!(parse_aspect("2.39:1"), Ok(2.39));
+    }
+
+    #[test]
+    fn rejects_invalid_aspect_ratios() {
+        for aspect in [
+            "", "16", "16x9", "16:", ":9", "0:1", "1:0", "-4:3", "inf:1", "a:b",
+        ] {
+            assert!(parse_aspect(aspect).is_err(), "{aspect}");
+        }
+    }
+
+    #[test]
+    fn parses_hex_colors_into_linear_values() {
+        assert_eq!(parse_hex_color("#000000"), Ok(wgpu::Color::BLACK));
+        assert_eq!(parse_hex_color("ffffff"), Ok(wgpu::Color::WHITE));
+
+        // sRGB 0x80 is about 0.216 linear, not 0.5
+        let color = parse_hex_color("#80Ff0a").unwrap();
+        assert!((color.r - 0.2158605).abs() < 1e-6, "{}", color.r);
+        assert_eq!(color.g, 1.0);
+        // below the linear segment's cutoff
+        assert!((color.b - 10.0 / 255.0 / 12.92).abs() < 1e-9, "{}", color.b);
+        assert_eq!(color.a, 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_hex_colors() {
+        for hex in [
+            "", "#", "#fff", "#1234567", "#12345g", "##123456", "0x123456",
+        ] {
+            assert!(parse_hex_color(hex).is_err(), "{hex}");
+        }
+    }
+
+    #[test]
+    fn parses_sample_counts() {
+        for count in [1, 2, 4, 8] {
+            assert_eq!(parse_sample_count(&count.to_string()), Ok(count));
+        }
+        for count in ["", "0", "3", "16", "-4", "four"] {
+            assert!(parse_sample_count(count).is_err(), "{count}");
+        }
+    }
+
+    #[test]
+    fn parses_feature_names_in_any_case() {
+        assert_eq!(
+            parse_feature("push_constants"),
+            Ok(wgpu::Features::PUSH_CONSTANTS)
+        );
+        assert_eq!(
+            parse_feature("POLYGON_MODE_LINE"),
+            Ok(wgpu::Features::POLYGON_MODE_LINE)
+        );
+        for name in ["", "push-constants", "push_constant", "all"] {
+            assert!(parse_feature(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn parses_grading_within_its_ranges() {
+        assert_eq!(parse_gamma("2.2"), Ok(2.2));
+        assert_eq!(parse_gamma("0.2"), Ok(0.2));
+        assert_eq!(parse_exposure("4"), Ok(4.0));
+        for gamma in ["", "0", "0.1", "5.5", "NaN", "x"] {
+            assert!(parse_gamma(gamma).is_err(), "{gamma}");
+        }
+        for exposure in ["", "0", "-1", "4.5", "NaN", "x"] {
+            assert!(parse_exposure(exposure).is_err(), "{exposure}");
+        }
+    }
+}
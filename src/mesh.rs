@@ -0,0 +1,308 @@
+use std::{borrow::Cow, path::Path, sync::Arc};
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    gfx::DEPTH_FORMAT,
+    obj::ObjMesh,
+    scene::{Scene, SceneContext},
+};
+
+/// the mesh shader, used by `--watch`
+pub const SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/mesh.wgsl");
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+    normal: [f32; 3],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x3];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// outward normal, up direction and color of each face
+const FACES: [(Vec3, Vec3, [f32; 3]); 6] = [
+    (Vec3::X, Vec3::Y, [1.0, 0.0, 0.0]),
+    (Vec3::NEG_X, Vec3::Y, [0.0, 1.0, 1.0]),
+    (Vec3::Y, Vec3::NEG_Z, [1.0, 1.0, 1.0]),
+    (Vec3::NEG_Y, Vec3::Z, [1.0, 0.0, 1.0]),
+    (Vec3::Z, Vec3::Y, [0.0, 0.0, 1.0]),
+    (Vec3::NEG_Z, Vec3::Y, [1.0, 1.0, 0.0]),
+];
+
+/// a unit cube around the origin, 4 vertices per face so every face has its own color. the
+/// triangles are counter-clockwise seen from outside, so culling back faces with the default
+/// winding keeps the ones facing the camera
+fn cube() -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(FACES.len() * 4);
+    let mut indices = Vec::with_capacity(FACES.len() * 6);
+    for (normal, up, color) in FACES {
+        let right = up.cross(normal);
+        let first = vertices.len() as u32;
+        for corner in [-right - up, right - up, right + up, -right + up] {
+            vertices.push(Vertex {
+                position: ((normal + corner) * 0.5).to_array(),
+                color,
+                normal: normal.to_array(),
+            });
+        }
+        indices.extend([0, 1, 2, 2, 3, 0].map(|i| first + i));
+    }
+    (vertices, indices)
+}
+
+/// models don't have colors of their own
+const MODEL_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+
+/// `obj` scaled to fit into the unit cube, in the same color all over
+fn model(mut obj: ObjMesh) -> (Vec<Vertex>, Vec<u32>) {
+    obj.fit_unit_cube();
+    let vertices = obj
+        .positions
+        .iter()
+        .zip(&obj.normals)
+        .map(|(position, normal)| Vertex {
+            position: position.to_array(),
+            color: MODEL_COLOR,
+            normal: normal.to_array(),
+        })
+        .collect();
+    (vertices, obj.indices)
+}
+
+/// matches `Model` in mesh.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Model {
+    transform: [f32; 16],
+    /// inverse transpose of `transform`, keeps normals perpendicular to the faces even if the
+    /// transform doesn't scale uniformly
+    normal: [f32; 16],
+}
+
+/// matches `Light` in mesh.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Light {
+    /// towards the light in world space
+    direction: [f32; 3],
+    /// how bright the faces turned away from the light still are
+    ambient: f32,
+    color: [f32; 3],
+    _pad: f32,
+}
+
+/// a white light from the upper right front, fixed while the mesh turns underneath it
+const LIGHT: Light = Light {
+    direction: [0.6, 1.0, 0.8],
+    ambient: 0.2,
+    color: [1.0, 1.0, 1.0],
+    _pad: 0.0,
+};
+
+/// radians per second
+const ROTATION_SPEED: f32 = 1.0;
+
+/// a single spinning mesh, lit from a fixed direction
+pub struct MeshScene {
+    queue: Arc<wgpu::Queue>,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    model_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    angle: f32,
+}
+
+impl MeshScene {
+    /// a cube with a differently colored face on each side
+    pub fn cube(context: &SceneContext) -> Self {
+        let (vertices, indices) = cube();
+        Self::new(context, &vertices, &indices)
+    }
+
+    /// the obj file at `path`, centered and scaled to the size of the cube
+    pub fn load(context: &SceneContext, path: &Path) -> anyhow::Result<Self> {
+        let (vertices, indices) = model(ObjMesh::load(path)?);
+        Ok(Self::new(context, &vertices, &indices))
+    }
+
+    fn new(context: &SceneContext, vertices: &[Vertex], indices: &[u32]) -> Self {
+        let device = context.device;
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mesh vertices"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mesh indices"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let model_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("mesh model"),
+            size: std::mem::size_of::<Model>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mesh light"),
+            contents: bytemuck::bytes_of(&LIGHT),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("mesh bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mesh bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: model_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: light_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mesh pipeline layout"),
+            bind_group_layouts: &[context.frame_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = Self::create_pipeline(context, &pipeline_layout, include_str!("mesh.wgsl"));
+
+        Self {
+            queue: context.queue.clone(),
+            pipeline_layout,
+            pipeline,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            model_buffer,
+            bind_group,
+            angle: 0.0,
+        }
+    }
+
+    fn create_pipeline(
+        context: &SceneContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let device = context.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("mesh shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("mesh pipeline"),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: context.format,
+                        blend: context.blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(context.debug_format.into()),
+                ],
+            }),
+            primitive: context.primitive_state(Some(wgpu::IndexFormat::Uint32)),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: context.blend.is_none(),
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: context.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+impl Scene for MeshScene {
+    fn update(&mut self, dt: f32) {
+        self.angle = (self.angle + ROTATION_SPEED * dt) % std::f32::consts::TAU;
+
+        // tumbles around two axes at once so all six faces come into view
+        let transform = Mat4::from_rotation_y(self.angle) * Mat4::from_rotation_x(self.angle * 0.5);
+        let model = Model {
+            transform: transform.to_cols_array(),
+            normal: transform.inverse().transpose().to_cols_array(),
+        };
+        self.queue
+            .write_buffer(&self.model_buffer, 0, bytemuck::bytes_of(&model));
+    }
+
+    fn render(&mut self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(1, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        rpass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+
+    fn reload_shader(&mut self, context: &SceneContext, source: &str) -> anyhow::Result<()> {
+        self.pipeline = Self::create_pipeline(context, &self.pipeline_layout, source);
+        Ok(())
+    }
+}
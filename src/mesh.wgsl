@@ -0,0 +1,78 @@
+// matches `Globals` in shader.wgsl
+struct Globals {
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    time: f32,
+    scale_factor: f32,
+    // alpha of the faces, 1 unless blending them
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+@group(0) @binding(1)
+var<uniform> view_projection: mat4x4<f32>;
+
+struct Model {
+    // rotates the mesh around its center
+    transform: mat4x4<f32>,
+    // inverse transpose of `transform`, for the normals
+    normal: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> model: Model;
+
+struct Light {
+    // towards the light in world space
+    direction: vec3<f32>,
+    // brightness of the faces turned away from the light
+    ambient: f32,
+    color: vec3<f32>,
+}
+
+@group(1) @binding(1)
+var<uniform> light: Light;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    // in [0, 1] across the mesh's bounding box
+    @location(1) local: vec3<f32>,
+    // in world space, not normalized after interpolation
+    @location(2) normal: vec3<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // shown by the debug post-processing effect
+    @location(1) debug: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = view_projection * model.transform * vec4<f32>(in.position, 1.0);
+    out.color = in.color;
+    out.local = in.position + 0.5;
+    out.normal = (model.normal * vec4<f32>(in.normal, 0.0)).xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var out: FragmentOutput;
+    // lambert, the faces get darker the further they're turned away from the light
+    let diffuse = max(dot(normalize(in.normal), normalize(light.direction)), 0.0);
+    let lit = in.color * (light.ambient + diffuse * light.color);
+    out.color = vec4<f32>(lit, globals.opacity);
+    out.debug = vec4<f32>(in.local, 1.0);
+    return out;
+}
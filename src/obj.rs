@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use anyhow::Context;
+use glam::Vec3;
+
+/// a triangle mesh from a wavefront obj file, texture coordinates and materials are skipped
+pub struct ObjMesh {
+    pub positions: Vec<Vec3>,
+    /// one per position, from the file or the face normal where it has none
+    pub normals: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl ObjMesh {
+    /// reads the `v`, `vn` and `f` statements of the file at `path`, polygons are split into
+    /// triangle fans
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read model {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("failed to parse model {}", path.display()))
+    }
+
+    fn parse(source: &str) -> anyhow::Result<Self> {
+        let mut file_positions = Vec::new();
+        let mut file_normals = Vec::new();
+        let mut mesh = Self {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            indices: Vec::new(),
+        };
+        // corners with a normal are shared between faces, the others get their face's normal
+        let mut corners = std::collections::HashMap::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut tokens = line.split_whitespace();
+            let result = match tokens.next() {
+                Some("v") => parse_vec3(tokens).map(|v| file_positions.push(v)),
+                Some("vn") => parse_vec3(tokens).map(|v| file_normals.push(v)),
+                Some("f") => tokens
+                    .map(|corner| parse_corner(corner, file_positions.len(), file_normals.len()))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .and_then(|face| {
+                        mesh.add_face(&face, &file_positions, &file_normals, &mut corners)
+                    }),
+                // texture coordinates, groups, materials, lines and whatever else there is
+                _ => Ok(()),
+            };
+            result.with_context(|| format!("line {}", number + 1))?;
+        }
+
+        if mesh.indices.is_empty() {
+            Err(anyhow::anyhow!("there are no faces"))?;
+        }
+        Ok(mesh)
+    }
+
+    fn add_face(
+        &mut self,
+        face: &[(usize, Option<usize>)],
+        file_positions: &[Vec3],
+        file_normals: &[Vec3],
+        corners: &mut std::collections::HashMap<(usize, usize), u32>,
+    ) -> anyhow::Result<()> {
+        if face.len() < 3 {
+            Err(anyhow::anyhow!(
+                "a face needs at least 3 corners, not {}",
+                face.len()
+            ))?;
+        }
+        let [a, b, c] = [face[0].0, face[1].0, face[2].0].map(|i| file_positions[i]);
+        let face_normal = (b - a).cross(c - a).normalize_or_zero();
+
+        let indices = face
+            .iter()
+            .map(|&(position, normal)| {
+                let mut push = |normal| {
+                    self.positions.push(file_positions[position]);
+                    self.normals.push(normal);
+                    self.positions.len() as u32 - 1
+                };
+                match normal {
+                    Some(normal) => *corners
+                        .entry((position, normal))
+                        .or_insert_with(|| push(file_normals[normal])),
+                    None => push(face_normal),
+                }
+            })
+            .collect::<Vec<_>>();
+        for i in 1..indices.len() - 1 {
+            self.indices
+                .extend([indices[0], indices[i], indices[i + 1]]);
+        }
+        Ok(())
+    }
+
+    /// moves the center of the bounding box to the origin and scales its longest side to 1
+    pub fn fit_unit_cube(&mut self) {
+        let (min, max) = self.positions.iter().fold(
+            (Vec3::INFINITY, Vec3::NEG_INFINITY),
+            |(min, max), position| (min.min(*position), max.max(*position)),
+        );
+        let center = (min + max) * 0.5;
+        let size = (max - min).max_element();
+        let scale = if size > 0.0 { 1.0 / size } else { 1.0 };
+        for position in &mut self.positions {
+            *position = (*position - center) * scale;
+        }
+    }
+}
+
+fn parse_vec3<'a>(mut tokens: impl Iterator<Item = &'a str>) -> anyhow::Result<Vec3> {
+    let mut component = || -> anyhow::Result<f32> {
+        let token = tokens
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("expected 3 coordinates"))?;
+        token
+            .parse()
+            .with_context(|| format!("{token:?} is not a number"))
+    };
+    Ok(Vec3::new(component()?, component()?, component()?))
+}
+
+/// `v`, `v/vt`, `v//vn` or `v/vt/vn`, as indices into what was read so far
+fn parse_corner(
+    corner: &str,
+    position_count: usize,
+    normal_count: usize,
+) -> anyhow::Result<(usize, Option<usize>)> {
+    let mut indices = corner.split('/');
+    let position = resolve_index(indices.next().unwrap_or_default(), position_count)
+        .with_context(|| format!("invalid position in {corner:?}"))?;
+    let normal = match indices.nth(1) {
+        Some(normal) if !normal.is_empty() => Some(
+            resolve_index(normal, normal_count)
+                .with_context(|| format!("invalid normal in {corner:?}"))?,
+        ),
+        _ => None,
+    };
+    Ok((position, normal))
+}
+
+/// indices start at 1, negative ones count back from the last element
+fn resolve_index(index: &str, count: usize) -> anyhow::Result<usize> {
+    let index: isize = index
+        .parse()
+        .with_context(|| format!("{index:?} is not an index"))?;
+    let resolved = match index {
+        1.. => index as usize - 1,
+        ..=-1 => count
+            .checked_sub(index.unsigned_abs())
+            .ok_or_else(|| anyhow::anyhow!("{index} is out of range"))?,
+        0 => Err(anyhow::anyhow!("indices start at 1"))?,
+    };
+    if resolved >= count {
+        Err(anyhow::anyhow!(
+            "{index} is out of range, there are {count}"
+        ))?;
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_polygons_into_triangle_fans() {
+        let mesh = ObjMesh::parse(
+            "
+            # a unit square in the xy plane
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            f 1 2 3 4
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+        // counter-clockwise seen from +z
+        assert!(mesh.normals.iter().all(|normal| *normal == Vec3::Z));
+    }
+
+    #[test]
+    fn shares_corners_with_a_normal() {
+        let mesh = ObjMesh::parse(
+            "
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vn 0 0 -1
+            vt 0 0
+            f 1//1 2//1 3//1
+            f 1/1/1 3/1/1 -1//-1
+            ",
+        )
+        .unwrap();
+
+        // the first and third corner of the second face are the first face's
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+        assert!(mesh.normals.iter().all(|normal| *normal == Vec3::NEG_Z));
+    }
+
+    #[test]
+    fn skips_what_it_doesnt_draw() {
+        let mesh = ObjMesh::parse(
+            "
+            mtllib cube.mtl
+            o triangle
+            g group
+            usemtl red
+            s off
+            v 0 0 0 # comment
+            v 1 0 0
+            v 0 1 0
+            l 1 2
+            f 1 2 3
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        for (source, error) in [
+            ("v 0 0 0", "there are no faces"),
+            ("v 0 0\nf 1 1 1", "line 1"),
+            ("v 0 0 x", "line 1"),
+            ("v 0 0 0\nv 1 0 0\nf 1 2", "line 3"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4", "line 4"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 0", "line 4"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1//1 2//1 3//1", "line 4"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 x", "line 4"),
+        ] {
+            let result = ObjMesh::parse(source);
+            let message = format!("{:#}", result.err().expect(source));
+            assert!(message.contains(error), "{source:?}: {message}");
+        }
+    }
+
+    #[test]
+    fn fits_into_the_unit_cube() {
+        let mut mesh = ObjMesh::parse("v 0 0 0\nv 4 0 0\nv 0 2 0\nf 1 2 3").unwrap();
+        mesh.fit_unit_cube();
+
+        assert_eq!(
+            mesh.positions,
+            [
+                Vec3::new(-0.5, -0.25, 0.0),
+                Vec3::new(0.5, -0.25, 0.0),
+                Vec3::new(-0.5, 0.25, 0.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::{borrow::Cow, collections::HashMap, ops::RangeInclusive};
+
+use wgpu::util::DeviceExt;
+
+use crate::gfx::HDR_FORMAT;
+
+/// what the full-screen pass does to the scene on the way to the target
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Passthrough,
+    Grayscale,
+    Invert,
+    /// shows the scenes' second render target instead of their color
+    Debug,
+}
+
+impl Effect {
+    const ALL: [Self; 4] = [
+        Self::Passthrough,
+        Self::Grayscale,
+        Self::Invert,
+        Self::Debug,
+    ];
+
+    /// of the fragment shader in blit.wgsl
+    fn entry_point(self) -> &'static str {
+        match self {
+            Self::Passthrough => "fs_passthrough",
+            Self::Grayscale => "fs_grayscale",
+            Self::Invert => "fs_invert",
+            Self::Debug => "fs_debug",
+        }
+    }
+}
+
+/// the color correction of the full-screen pass, matches `Grading` in blit.wgsl. applied
+/// after tone mapping and before the effect
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Grading {
+    /// the colors are raised to 1 / gamma, above 1 brightens the dark parts
+    pub gamma: f32,
+    /// multiplied onto the colors after the gamma
+    pub exposure: f32,
+    /// rounds the size up to 16 bytes, like `_pad` in `Globals`
+    _pad: [f32; 2],
+}
+
+impl Grading {
+    pub const GAMMA_RANGE: RangeInclusive<f32> = 0.2..=5.0;
+    pub const EXPOSURE_RANGE: RangeInclusive<f32> = 0.1..=4.0;
+
+    /// clamps both into their ranges
+    pub fn new(gamma: f32, exposure: f32) -> Self {
+        let clamp =
+            |value: f32, range: RangeInclusive<f32>| value.clamp(*range.start(), *range.end());
+        Self {
+            gamma: clamp(gamma, Self::GAMMA_RANGE),
+            exposure: clamp(exposure, Self::EXPOSURE_RANGE),
+            _pad: [0.0; 2],
+        }
+    }
+}
+
+/// leaves the colors as they are
+impl Default for Grading {
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+/// a color texture the scene is rendered into instead of the surface, then drawn onto it by a
+/// full-screen pass that applies an `Effect`
+pub struct Offscreen {
+    /// of the texture, not necessarily the target's
+    format: wgpu::TextureFormat,
+    /// one per effect, in the order of `Effect::ALL`
+    pipelines: Vec<wgpu::RenderPipeline>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// holds a `Grading`
+    grading_buffer: wgpu::Buffer,
+    grading_bind_group: wgpu::BindGroup,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Offscreen {
+    /// `format` is the texture's, usually the same as `target_format` so the copy doesn't
+    /// change the colors. `HDR_FORMAT` is tone mapped into the target's range instead, even
+    /// when the target is `HDR_FORMAT` too.
+    /// `debug_view` is the scenes' second render target, for `Effect::Debug`
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        target_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        debug_view: &wgpu::TextureView,
+        grading: Grading,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("offscreen bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        // a group of its own, it outlives the texture's bind group when resizing
+        let grading_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("grading bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let grading_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("grading"),
+            contents: bytemuck::bytes_of(&grading),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let grading_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("grading bind group"),
+            layout: &grading_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: grading_buffer.as_entire_binding(),
+            }],
+        });
+        // nearest filtering, every pixel samples exactly one texel when the texture is as big as
+        // the target and a fixed internal resolution stays pixelated when it's upscaled
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("offscreen sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("offscreen pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout, &grading_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("blit shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("blit.wgsl"))),
+        });
+        let tone_map = format == HDR_FORMAT;
+        let constants = HashMap::from([("TONE_MAP".to_string(), f64::from(u8::from(tone_map)))]);
+        let pipelines = Effect::ALL
+            .iter()
+            .map(|effect| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(effect.entry_point()),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: Some("vs_main"),
+                        buffers: &[],
+                        compilation_options: Default::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: Some(effect.entry_point()),
+                        compilation_options: wgpu::PipelineCompilationOptions {
+                            constants: &constants,
+                            ..Default::default()
+                        },
+                        targets: &[Some(target_format.into())],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                    cache: None,
+                })
+            })
+            .collect();
+
+        let (view, bind_group) = Self::create_texture(
+            device,
+            format,
+            &bind_group_layout,
+            &sampler,
+            width,
+            height,
+            debug_view,
+        );
+        Self {
+            format,
+            pipelines,
+            bind_group_layout,
+            sampler,
+            grading_buffer,
+            grading_bind_group,
+            view,
+            bind_group,
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        width: u32,
+        height: u32,
+        debug_view: &wgpu::TextureView,
+    ) -> (wgpu::TextureView, wgpu::BindGroup) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("offscreen bind group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(debug_view),
+                },
+            ],
+        });
+        (view, bind_group)
+    }
+
+    /// the texture has to match the size of the scene's other targets, `debug_view` is the
+    /// resized one
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        debug_view: &wgpu::TextureView,
+    ) {
+        (self.view, self.bind_group) = Self::create_texture(
+            device,
+            self.format,
+            &self.bind_group_layout,
+            &self.sampler,
+            width,
+            height,
+            debug_view,
+        );
+    }
+
+    /// used from the next submission on
+    pub fn set_grading(&self, queue: &wgpu::Queue, grading: Grading) {
+        queue.write_buffer(&self.grading_buffer, 0, bytemuck::bytes_of(&grading));
+    }
+
+    /// what the scene is rendered into, or resolved into with multisampling
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// draws the texture onto `target` with `effect`, after the pass that renders into it.
+    /// `viewport` is the x, y, width and height in pixels of where it ends up, the rest of
+    /// `target` stays black
+    pub fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        effect: Effect,
+        viewport: [f32; 4],
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("blit pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    // the bars around the viewport, every pixel inside it is overwritten
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        let index = Effect::ALL.iter().position(|e| *e == effect).unwrap();
+        rpass.set_pipeline(&self.pipelines[index]);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_bind_group(1, &self.grading_bind_group, &[]);
+        let [x, y, width, height] = viewport;
+        rpass.set_viewport(x, y, width, height, 0.0, 1.0);
+        rpass.draw(0..3, 0..1);
+    }
+}
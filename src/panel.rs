@@ -0,0 +1,359 @@
+use std::ops::RangeInclusive;
+
+use glam::Vec2;
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, MouseButton, WindowEvent},
+};
+
+use crate::text::TextRenderer;
+
+/// what the debug panel's sliders change
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanelValues {
+    /// the current clear color, linear
+    pub clear_color: [f32; 3],
+    /// how fast the scene animates, its rotation, 1 is the normal speed
+    pub speed: f32,
+}
+
+struct Slider {
+    label: &'static str,
+    range: RangeInclusive<f32>,
+    /// of the filled part of the track
+    color: [f32; 4],
+    value: fn(&mut PanelValues) -> &mut f32,
+}
+
+const SLIDERS: [Slider; 4] = [
+    Slider {
+        label: "red",
+        range: 0.0..=1.0,
+        color: [0.8, 0.1, 0.1, 1.0],
+        value: |values| &mut values.clear_color[0],
+    },
+    Slider {
+        label: "green",
+        range: 0.0..=1.0,
+        color: [0.1, 0.6, 0.1, 1.0],
+        value: |values| &mut values.clear_color[1],
+    },
+    Slider {
+        label: "blue",
+        range: 0.0..=1.0,
+        color: [0.1, 0.2, 0.9, 1.0],
+        value: |values| &mut values.clear_color[2],
+    },
+    Slider {
+        label: "speed",
+        range: 0.0..=4.0,
+        color: [0.7, 0.7, 0.7, 1.0],
+        value: |values| &mut values.speed,
+    },
+];
+/// the title and the framerate come before the sliders
+const HEADER_ROWS: usize = 2;
+
+/// where the parts of the panel are, in physical pixels. everything is measured in pixels of
+/// the font, which are `scale` physical pixels big
+struct Layout {
+    /// top left corner of the panel
+    origin: Vec2,
+    size: Vec2,
+    scale: f32,
+}
+
+impl Layout {
+    const PADDING: f32 = 4.0;
+    const ROW_HEIGHT: f32 = 11.0;
+    /// room for the longest label
+    const LABEL_WIDTH: f32 = 36.0;
+    const TRACK_WIDTH: f32 = 100.0;
+    /// room for a value like `4.00`
+    const VALUE_WIDTH: f32 = 30.0;
+
+    /// in the top right corner of a target of `size`, the hud has the left one
+    fn new(size: PhysicalSize<u32>, scale: f32) -> Self {
+        let rows = (HEADER_ROWS + SLIDERS.len()) as f32;
+        let panel = Vec2::new(
+            Self::LABEL_WIDTH + Self::TRACK_WIDTH + Self::VALUE_WIDTH,
+            rows * Self::ROW_HEIGHT,
+        ) + Self::PADDING * 2.0;
+        let origin = Vec2::new(
+            size.width as f32 - (panel.x + Self::PADDING) * scale,
+            Self::PADDING * scale,
+        );
+        Self {
+            origin,
+            size: panel * scale,
+            scale,
+        }
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        let max = self.origin + self.size;
+        point.cmpge(self.origin).all() && point.cmplt(max).all()
+    }
+
+    /// top left corner of the text in `row`
+    fn row(&self, row: usize) -> Vec2 {
+        self.origin
+            + Vec2::new(
+                Self::PADDING,
+                Self::PADDING + row as f32 * Self::ROW_HEIGHT + 2.0,
+            ) * self.scale
+    }
+
+    /// top left corner and size of a slider's track, as high as the font
+    fn track(&self, slider: usize) -> (Vec2, Vec2) {
+        let min = self.row(HEADER_ROWS + slider) + Vec2::new(Self::LABEL_WIDTH, 0.0) * self.scale;
+        (min, Vec2::new(Self::TRACK_WIDTH, 7.0) * self.scale)
+    }
+
+    /// the slider whose row `point` is in, pressing anywhere next to a track grabs it
+    fn slider_at(&self, point: Vec2) -> Option<usize> {
+        (0..SLIDERS.len()).find(|&slider| {
+            let (min, size) = self.track(slider);
+            let top = min.y - 2.0 * self.scale;
+            let bottom = top + Self::ROW_HEIGHT * self.scale;
+            (top..bottom).contains(&point.y)
+                && (min.x - Self::PADDING * self.scale..min.x + size.x + Self::PADDING * self.scale)
+                    .contains(&point.x)
+        })
+    }
+
+    /// what the slider is set to with the cursor at `x`, clamped to its range
+    fn value_at(&self, slider: usize, x: f32) -> f32 {
+        let (min, size) = self.track(slider);
+        let t = ((x - min.x) / size.x).clamp(0.0, 1.0);
+        let range = &SLIDERS[slider].range;
+        range.start() + t * (range.end() - range.start())
+    }
+}
+
+/// an overlay with sliders for the clear color and the animation speed and the framerate,
+/// toggled with f1. it takes the mouse events it uses before the rest of the app sees them
+pub struct DebugPanel {
+    pub visible: bool,
+    /// last known cursor position, in physical pixels
+    cursor: Option<Vec2>,
+    /// the left button was pressed over the panel, so its release is the panel's too
+    pressed: bool,
+    /// the slider that follows the cursor while the button is held
+    dragging: Option<usize>,
+}
+
+impl DebugPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            cursor: None,
+            pressed: false,
+            dragging: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.pressed = false;
+        self.dragging = None;
+    }
+
+    /// true if `event` was meant for the panel and shouldn't do anything else, `values`
+    /// change while a slider is dragged. `size` and `scale` are the target's and the font's
+    pub fn event(
+        &mut self,
+        event: &WindowEvent,
+        size: PhysicalSize<u32>,
+        scale: f32,
+        values: &mut PanelValues,
+    ) -> bool {
+        let layout = Layout::new(size, scale);
+        match event {
+            // the rest of the app keeps track of the cursor as well
+            WindowEvent::CursorMoved { position, .. } => {
+                self.move_cursor(
+                    &layout,
+                    Vec2::new(position.x as f32, position.y as f32),
+                    values,
+                );
+                false
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                false
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => self.press(&layout, values),
+                ElementState::Released => self.release(),
+            },
+            _ => false,
+        }
+    }
+
+    fn move_cursor(&mut self, layout: &Layout, position: Vec2, values: &mut PanelValues) {
+        self.cursor = Some(position);
+        if let Some(slider) = self.dragging {
+            *(SLIDERS[slider].value)(values) = layout.value_at(slider, position.x);
+        }
+    }
+
+    fn press(&mut self, layout: &Layout, values: &mut PanelValues) -> bool {
+        let Some(cursor) = self
+            .cursor
+            .filter(|cursor| self.visible && layout.contains(*cursor))
+        else {
+            return false;
+        };
+        self.pressed = true;
+        self.dragging = layout.slider_at(cursor);
+        if let Some(slider) = self.dragging {
+            *(SLIDERS[slider].value)(values) = layout.value_at(slider, cursor.x);
+        }
+        true
+    }
+
+    fn release(&mut self) -> bool {
+        self.dragging = None;
+        std::mem::take(&mut self.pressed)
+    }
+
+    /// queues the panel into `text`, see `DebugPanel::event` for `size` and `scale`
+    pub fn draw(
+        &self,
+        text: &mut TextRenderer,
+        size: PhysicalSize<u32>,
+        scale: f32,
+        mut values: PanelValues,
+        fps: &str,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let layout = Layout::new(size, scale);
+        let white = [1.0; 4];
+        text.rect(layout.origin, layout.size, [0.05, 0.05, 0.05, 0.8]);
+        text.text(layout.row(0), scale, white, "debug panel (F1)");
+        text.text(layout.row(1), scale, white, fps);
+
+        for (i, slider) in SLIDERS.iter().enumerate() {
+            let value = *(slider.value)(&mut values);
+            let t = (value - slider.range.start()) / (slider.range.end() - slider.range.start());
+            let (min, track) = layout.track(i);
+            text.text(layout.row(HEADER_ROWS + i), scale, white, slider.label);
+            text.rect(min, track, [0.25, 0.25, 0.25, 1.0]);
+            text.rect(min, Vec2::new(track.x * t, track.y), slider.color);
+            // the handle sticks out of the track a little
+            let handle = Vec2::new(3.0, 9.0) * scale;
+            text.rect(
+                Vec2::new(min.x + track.x * t - handle.x / 2.0, min.y - scale),
+                handle,
+                white,
+            );
+            text.text(
+                min + Vec2::new(track.x + 6.0 * scale, 0.0),
+                scale,
+                white,
+                &format!("{value:.2}"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: PhysicalSize<u32> = PhysicalSize::new(1280, 720);
+
+    fn values() -> PanelValues {
+        PanelValues {
+            clear_color: [0.1, 0.2, 0.3],
+            speed: 1.0,
+        }
+    }
+
+    /// the middle of a slider's track
+    fn on_track(layout: &Layout, slider: usize, t: f32) -> Vec2 {
+        let (min, size) = layout.track(slider);
+        min + size * Vec2::new(t, 0.5)
+    }
+
+    #[test]
+    fn sits_in_the_top_right_corner() {
+        let layout = Layout::new(SIZE, 2.0);
+
+        assert_eq!(layout.origin.x + layout.size.x, 1280.0 - 8.0);
+        assert_eq!(layout.origin.y, 8.0);
+        assert!(layout.contains(layout.origin));
+        assert!(!layout.contains(layout.origin + layout.size));
+        assert!(!layout.contains(Vec2::new(10.0, 10.0)));
+    }
+
+    #[test]
+    fn dragging_a_slider_sets_its_value() {
+        let layout = Layout::new(SIZE, 2.0);
+        let mut panel = DebugPanel::new();
+        panel.toggle();
+        let mut values = values();
+
+        panel.move_cursor(&layout, on_track(&layout, 3, 0.5), &mut values);
+        assert!(panel.press(&layout, &mut values));
+        assert_eq!(values.speed, 2.0);
+
+        // past the end of the track it stays at the end of the range
+        panel.move_cursor(&layout, on_track(&layout, 3, 2.0), &mut values);
+        assert_eq!(values.speed, 4.0);
+        assert!(panel.release());
+
+        // released, moving doesn't change anything anymore
+        panel.move_cursor(&layout, on_track(&layout, 3, 0.0), &mut values);
+        assert_eq!(values.speed, 4.0);
+        assert_eq!(values.clear_color, [0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn each_slider_changes_its_own_value() {
+        let layout = Layout::new(SIZE, 1.0);
+        let mut panel = DebugPanel::new();
+        panel.toggle();
+        let mut values = values();
+
+        for slider in 0..3 {
+            panel.move_cursor(&layout, on_track(&layout, slider, 1.0), &mut values);
+            panel.press(&layout, &mut values);
+            panel.release();
+        }
+        assert_eq!(values.clear_color, [1.0; 3]);
+        assert_eq!(values.speed, 1.0);
+    }
+
+    #[test]
+    fn leaves_clicks_elsewhere_to_the_app() {
+        let layout = Layout::new(SIZE, 2.0);
+        let mut panel = DebugPanel::new();
+        let mut values = values();
+
+        // hidden, even a click on it isn't the panel's
+        panel.move_cursor(&layout, on_track(&layout, 0, 0.5), &mut values);
+        assert!(!panel.press(&layout, &mut values));
+        assert!(!panel.release());
+
+        panel.toggle();
+        panel.move_cursor(&layout, Vec2::new(100.0, 400.0), &mut values);
+        assert!(!panel.press(&layout, &mut values));
+        // a drag that started outside ends outside too, wherever it's released
+        panel.move_cursor(&layout, on_track(&layout, 0, 0.5), &mut values);
+        assert!(!panel.release());
+
+        // on the panel but not on a slider, it's taken without changing anything
+        panel.move_cursor(&layout, layout.row(0), &mut values);
+        assert!(panel.press(&layout, &mut values));
+        assert!(panel.release());
+        assert_eq!(values, self::values());
+    }
+}
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+/// width and height of the texture
+const SIZE: u32 = 256;
+/// matches `@workgroup_size` in pattern.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+/// matches `Params` in pattern.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    /// seconds, animates the pattern
+    time: f32,
+    _pad: [f32; 3],
+}
+
+/// an animated texture that a compute shader draws into every frame, for `--pattern`
+pub struct PatternTexture {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    params_buffer: wgpu::Buffer,
+    view: wgpu::TextureView,
+}
+
+impl PatternTexture {
+    pub fn new(device: &wgpu::Device) -> Self {
+        // srgb formats can't be storage textures, the shader writes linear values
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("pattern texture"),
+            size: wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            // written by the compute pass, then sampled by the render pass. wgpu inserts the
+            // barrier between the two
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pattern params"),
+            size: std::mem::size_of::<Params>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pattern bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pattern bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pattern pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = Self::create_compute_pipeline(device, &pipeline_layout);
+
+        Self {
+            pipeline,
+            bind_group,
+            params_buffer,
+            view,
+        }
+    }
+
+    fn create_compute_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+    ) -> wgpu::ComputePipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pattern shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("pattern.wgsl"))),
+        });
+
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("pattern pipeline"),
+            layout: Some(pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        })
+    }
+
+    /// to be sampled in the render pass after `encode`
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, time: f32) {
+        let params = Params {
+            time,
+            _pad: [0.0; 3],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    /// redraws the texture, has to be recorded before the render pass that samples it
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("pattern pass"),
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &self.bind_group, &[]);
+        let workgroups = SIZE.div_ceil(WORKGROUP_SIZE);
+        cpass.dispatch_workgroups(workgroups, workgroups, 1);
+    }
+}
@@ -0,0 +1,27 @@
+struct Params {
+    // seconds, animates the pattern
+    time: f32,
+}
+
+@group(0) @binding(0)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+@group(0) @binding(1)
+var<uniform> params: Params;
+
+// rings moving outwards over a checkerboard, one invocation per texel
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(output);
+    // the last workgroups can reach past the edge
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+
+    let uv = vec2<f32>(id.xy) / vec2<f32>(size);
+    let rings = 0.5 + 0.5 * sin(distance(uv, vec2<f32>(0.5)) * 40.0 - params.time * 4.0);
+    let checker = f32((id.x / 32u + id.y / 32u) % 2u);
+    let color = mix(vec3<f32>(0.1, 0.2, 0.6), vec3<f32>(1.0, 0.6, 0.1), rings) * mix(0.6, 1.0, checker);
+
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(color, 1.0));
+}
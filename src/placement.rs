@@ -0,0 +1,155 @@
+use anyhow::Context;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    monitor::MonitorHandle,
+};
+
+/// where the first window's placement is kept between runs, next to the config
+pub const PLACEMENT_PATH: &str = "window.toml";
+
+/// where the first window was and how big it was when it was closed, it reopens there. saved as
+///
+/// ```toml
+/// x = 100
+/// y = 50
+/// width = 1280
+/// height = 720
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct WindowPlacement {
+    /// of the window's outer top left corner on the desktop, in physical pixels
+    pub position: PhysicalPosition<i32>,
+    pub inner_size: PhysicalSize<u32>,
+}
+
+impl WindowPlacement {
+    /// `None` if nothing was saved yet, a file that can't be read only warns
+    pub fn load() -> Option<Self> {
+        let source = match std::fs::read_to_string(PLACEMENT_PATH) {
+            Ok(source) => source,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                log::warn!("failed to read {PLACEMENT_PATH}: {err}");
+                return None;
+            }
+        };
+        match Self::parse(&source) {
+            Ok(placement) => Some(placement),
+            Err(err) => {
+                log::warn!("ignoring {PLACEMENT_PATH}: {err:#}");
+                None
+            }
+        }
+    }
+
+    fn parse(source: &str) -> anyhow::Result<Self> {
+        let document = source.parse::<toml_edit::DocumentMut>()?;
+        let integer = |key: &str| {
+            document
+                .get(key)
+                .and_then(toml_edit::Item::as_integer)
+                .ok_or_else(|| anyhow::anyhow!("`{key}` has to be an integer"))
+        };
+        let coordinate = |key: &str| {
+            i32::try_from(integer(key)?).with_context(|| format!("`{key}` is out of range"))
+        };
+        let dimension = |key: &str| {
+            u32::try_from(integer(key)?)
+                .ok()
+                .filter(|value| *value > 0)
+                .ok_or_else(|| anyhow::anyhow!("`{key}` has to be a positive integer"))
+        };
+
+        Ok(Self {
+            position: PhysicalPosition::new(coordinate("x")?, coordinate("y")?),
+            inner_size: PhysicalSize::new(dimension("width")?, dimension("height")?),
+        })
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut document = toml_edit::DocumentMut::new();
+        document["x"] = toml_edit::value(i64::from(self.position.x));
+        document["y"] = toml_edit::value(i64::from(self.position.y));
+        document["width"] = toml_edit::value(i64::from(self.inner_size.width));
+        document["height"] = toml_edit::value(i64::from(self.inner_size.height));
+        std::fs::write(PLACEMENT_PATH, document.to_string())
+            .with_context(|| format!("failed to write {PLACEMENT_PATH}"))
+    }
+
+    /// whether the top left corner is on one of `monitors`. one that was unplugged since can
+    /// leave the window somewhere it can't be dragged back from
+    pub fn is_on_any(&self, monitors: impl IntoIterator<Item = MonitorHandle>) -> bool {
+        monitors.into_iter().any(|monitor| {
+            let origin = monitor.position();
+            let size = monitor.size();
+            (origin.x..origin.x + size.width as i32).contains(&self.position.x)
+                && (origin.y..origin.y + size.height as i32).contains(&self.position.y)
+        })
+    }
+
+    /// the position that centers a window of `inner_size` on `monitor`, ignoring its decorations
+    pub fn centered(
+        monitor: &MonitorHandle,
+        inner_size: PhysicalSize<u32>,
+    ) -> PhysicalPosition<i32> {
+        let origin = monitor.position();
+        let size = monitor.size();
+        PhysicalPosition::new(
+            origin.x + (size.width as i32 - inner_size.width as i32) / 2,
+            origin.y + (size.height as i32 - inner_size.height as i32) / 2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_what_it_saves() {
+        let placement =
+            WindowPlacement::parse("x = -100\ny = 50\nwidth = 1280\nheight = 720").unwrap();
+
+        assert_eq!(placement.position, PhysicalPosition::new(-100, 50));
+        assert_eq!(placement.inner_size, PhysicalSize::new(1280, 720));
+    }
+
+    #[test]
+    fn rejects_invalid_placements() {
+        for (source, error) in [
+            ("y = 0\nwidth = 1\nheight = 1", "`x` has to be an integer"),
+            (
+                "x = 0.5\ny = 0\nwidth = 1\nheight = 1",
+                "`x` has to be an integer",
+            ),
+            (
+                "x = 0\ny = 3000000000\nwidth = 1\nheight = 1",
+                "`y` is out of range",
+            ),
+            (
+                "x = 0\ny = 0\nwidth = 0\nheight = 1",
+                "`width` has to be a positive integer",
+            ),
+            (
+                "x = 0\ny = 0\nwidth = 1\nheight = -1",
+                "`height` has to be a positive integer",
+            ),
+        ] {
+            let result = WindowPlacement::parse(source);
+            let message = result.expect_err(source).to_string();
+            assert_eq!(message, error, "{source:?}");
+        }
+        assert!(WindowPlacement::parse("x = ").is_err());
+    }
+
+    #[test]
+    fn ignores_unknown_keys() {
+        // it's only ever written by `save`, a newer version's extra keys shouldn't lose the rest
+        let placement =
+            WindowPlacement::parse("x = 1\ny = 2\nwidth = 3\nheight = 4\nmaximized = true")
+                .unwrap();
+
+        assert_eq!(placement.position, PhysicalPosition::new(1, 2));
+        assert_eq!(placement.inner_size, PhysicalSize::new(3, 4));
+    }
+}
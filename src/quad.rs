@@ -0,0 +1,639 @@
+use std::{borrow::Cow, path::Path, sync::Arc};
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    gfx::DEPTH_FORMAT,
+    pattern::PatternTexture,
+    scene::{Scene, SceneContext},
+};
+
+/// the quads' shader, used by `--watch`
+pub const SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl");
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+    uv: [f32; 2],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 3 => Float32x2];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// per instance offset in world space
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceData {
+    offset: [f32; 2],
+}
+
+impl InstanceData {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![2 => Float32x2];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+
+    /// centers of the cells of the smallest square grid that fits `count` instances,
+    /// filled row by row from the top left
+    fn grid(count: u32) -> (Vec<Self>, u32) {
+        let size = (count as f32).sqrt().ceil().max(1.0) as u32;
+        let cell = 2.0 / size as f32;
+        let instances = (0..count)
+            .map(|i| Self {
+                offset: [
+                    -1.0 + cell * ((i % size) as f32 + 0.5),
+                    1.0 - cell * ((i / size) as f32 + 0.5),
+                ],
+            })
+            .collect();
+        (instances, size)
+    }
+}
+
+const VERTICES: &[Vertex] = &[
+    Vertex {
+        position: [-1.0, -1.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+        uv: [0.0, 1.0],
+    },
+    Vertex {
+        position: [1.0, -1.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+        uv: [1.0, 1.0],
+    },
+    Vertex {
+        position: [1.0, 1.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+        uv: [1.0, 0.0],
+    },
+    Vertex {
+        position: [-1.0, 1.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+        uv: [0.0, 0.0],
+    },
+];
+
+/// two counter-clockwise triangles sharing the quad's diagonal
+const INDICES: &[u16] = &[0, 1, 2, 2, 3, 0];
+/// the same two triangles as a strip, which only needs each corner once. every second
+/// triangle of a strip is flipped before culling, so both stay counter-clockwise
+const STRIP_INDICES: &[u16] = &[0, 1, 3, 2];
+
+/// matches `Model` in shader.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Model {
+    /// applied before the instance offset, so every instance spins around its own center
+    transform: [f32; 16],
+    /// 1.0 tints the texture with the animated vertex color, 0.0 shows it as is
+    tint: f32,
+    _pad: [f32; 3],
+}
+
+/// radians per second
+const ROTATION_SPEED: f32 = 1.0;
+
+/// the address space of the frame tint in shader.wgsl, replaced by `FRAME_TINT_UNIFORM` when
+/// the device doesn't have push constants. only the part up to the name, so the rest of the
+/// declaration can be formatted any way
+const FRAME_TINT_PUSH_CONSTANT: &str = "var<push_constant>";
+const FRAME_TINT_UNIFORM: &str = "@group(1) @binding(3) var<uniform>";
+
+/// what the quads write into the stencil buffer, their outline is only drawn where it isn't
+const OUTLINE_STENCIL: u32 = 1;
+
+/// a grid of spinning quads, textured with a png or the compute shader's pattern
+pub struct QuadScene {
+    queue: Arc<wgpu::Queue>,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::RenderPipeline,
+    /// draws the quads a bit larger around the stencil they leave, `None` without `--outline`
+    outline_pipeline: Option<wgpu::RenderPipeline>,
+    vertex_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    instance_count: u32,
+    /// number of grid cells along each axis
+    grid_size: u32,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    model_buffer: wgpu::Buffer,
+    /// holds the frame tint if it can't be pushed, `None` with push constants
+    frame_tint_buffer: Option<wgpu::Buffer>,
+    /// multiplied onto every pixel, pulses over time
+    frame_tint: [f32; 4],
+    bind_group: wgpu::BindGroup,
+    /// width / height of the texture, the quad is stretched to match. `None` without one
+    texture_aspect: Option<f32>,
+    /// redrawn before every frame and sampled instead of a png
+    pattern: Option<PatternTexture>,
+    angle: f32,
+    /// seconds, animates the pattern
+    time: f32,
+}
+
+impl QuadScene {
+    /// `instances` quads laid out in a grid, with the png at `texture` or the animated
+    /// `pattern` drawn onto them, and an `outline` around each
+    pub fn new(
+        context: &SceneContext,
+        instances: u32,
+        texture: Option<&Path>,
+        pattern: bool,
+        outline: bool,
+    ) -> anyhow::Result<Self> {
+        let device = context.device;
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("quad vertices"),
+            contents: bytemuck::cast_slice(VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let (instance_data, grid_size) = InstanceData::grid(instances);
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("quad instances"),
+            contents: bytemuck::cast_slice(&instance_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let indices = match context.primitive.topology {
+            wgpu::PrimitiveTopology::TriangleStrip => STRIP_INDICES,
+            _ => INDICES,
+        };
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("quad indices"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        // taken from the uploaded data, the buffer itself may be padded to COPY_BUFFER_ALIGNMENT
+        let index_count = indices.len() as u32;
+
+        let model_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("quad model"),
+            size: std::mem::size_of::<Model>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let frame_tint_buffer = (!context.push_constants).then(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("quad frame tint"),
+                size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        let uniform = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let mut entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            uniform(2, wgpu::ShaderStages::VERTEX_FRAGMENT),
+        ];
+        if frame_tint_buffer.is_some() {
+            entries.push(uniform(3, wgpu::ShaderStages::FRAGMENT));
+        }
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("quad bind group layout"),
+            entries: &entries,
+        });
+
+        let pattern = pattern.then(|| PatternTexture::new(device));
+        let (bind_group, texture_aspect) = match pattern.as_ref() {
+            Some(pattern) => (
+                Self::create_bind_group(
+                    context,
+                    &bind_group_layout,
+                    pattern.view(),
+                    &model_buffer,
+                    frame_tint_buffer.as_ref(),
+                ),
+                Some(1.0),
+            ),
+            None => {
+                let image = match texture {
+                    Some(path) => Self::load_texture(device, path)?,
+                    // without a texture the shader samples a single white pixel
+                    None => image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])),
+                };
+                let view = Self::upload_texture(device, context.queue, &image);
+                (
+                    Self::create_bind_group(
+                        context,
+                        &bind_group_layout,
+                        &view,
+                        &model_buffer,
+                        frame_tint_buffer.as_ref(),
+                    ),
+                    texture.map(|_| image.width() as f32 / image.height() as f32),
+                )
+            }
+        };
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("quad pipeline layout"),
+            bind_group_layouts: &[context.frame_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: match frame_tint_buffer {
+                Some(_) => &[],
+                None => &[wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::FRAGMENT,
+                    range: 0..std::mem::size_of::<[f32; 4]>() as u32,
+                }],
+            },
+        });
+        let (pipeline, outline_pipeline) = Self::create_pipelines(
+            context,
+            &pipeline_layout,
+            include_str!("shader.wgsl"),
+            outline,
+        )?;
+
+        Ok(Self {
+            queue: context.queue.clone(),
+            pipeline_layout,
+            pipeline,
+            outline_pipeline,
+            vertex_buffer,
+            instance_buffer,
+            instance_count: instances,
+            grid_size,
+            index_buffer,
+            index_count,
+            model_buffer,
+            frame_tint_buffer,
+            frame_tint: [1.0; 4],
+            bind_group,
+            texture_aspect,
+            pattern,
+            angle: 0.0,
+            time: 0.0,
+        })
+    }
+
+    /// the quads' pipeline and, with `outline`, the one for their outline. the quads mark
+    /// where they are in the stencil buffer, and the outline is drawn everywhere else
+    fn create_pipelines(
+        context: &SceneContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader_source: &str,
+        outline: bool,
+    ) -> anyhow::Result<(wgpu::RenderPipeline, Option<wgpu::RenderPipeline>)> {
+        let device = context.device;
+        let shader_source = match context.push_constants {
+            true => Cow::Borrowed(shader_source),
+            // a declaration the replace doesn't find would leave a push constant the pipeline
+            // layout has no range for
+            false => match shader_source.matches(FRAME_TINT_PUSH_CONSTANT).count() {
+                1 => {
+                    Cow::Owned(shader_source.replace(FRAME_TINT_PUSH_CONSTANT, FRAME_TINT_UNIFORM))
+                }
+                count => Err(anyhow::anyhow!(
+                    "the quad shader has to declare the frame tint as its only \
+                     `{FRAME_TINT_PUSH_CONSTANT}`, it has {count}"
+                ))?,
+            },
+        };
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("quad shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_source),
+        });
+
+        // both faces, the quads aren't culled by default
+        let stencil = |compare, pass_op, write_mask| {
+            let face = wgpu::StencilFaceState {
+                compare,
+                fail_op: wgpu::StencilOperation::Keep,
+                depth_fail_op: wgpu::StencilOperation::Keep,
+                pass_op,
+            };
+            wgpu::StencilState {
+                front: face,
+                back: face,
+                read_mask: 0xff,
+                write_mask,
+            }
+        };
+        let quads = Self::create_pipeline(
+            context,
+            pipeline_layout,
+            &shader,
+            "vs_main",
+            "fs_main",
+            context.blend.is_none(),
+            match outline {
+                true => stencil(
+                    wgpu::CompareFunction::Always,
+                    wgpu::StencilOperation::Replace,
+                    0xff,
+                ),
+                false => wgpu::StencilState::default(),
+            },
+        );
+        let outline = outline.then(|| {
+            Self::create_pipeline(
+                context,
+                pipeline_layout,
+                &shader,
+                "vs_outline",
+                "fs_outline",
+                false,
+                stencil(
+                    wgpu::CompareFunction::NotEqual,
+                    wgpu::StencilOperation::Keep,
+                    0,
+                ),
+            )
+        });
+        Ok((quads, outline))
+    }
+
+    fn create_pipeline(
+        context: &SceneContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        vertex_entry_point: &str,
+        fragment_entry_point: &str,
+        depth_write_enabled: bool,
+        stencil: wgpu::StencilState,
+    ) -> wgpu::RenderPipeline {
+        context
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(fragment_entry_point),
+                layout: Some(pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: Some(vertex_entry_point),
+                    buffers: &[Vertex::layout(), InstanceData::layout()],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: Some(fragment_entry_point),
+                    compilation_options: Default::default(),
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: context.format,
+                            blend: context.blend,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }),
+                        Some(context.debug_format.into()),
+                    ],
+                }),
+                primitive: context.primitive_state(Some(wgpu::IndexFormat::Uint16)),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil,
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: context.sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+    }
+
+    /// decodes the image at `path`, it has to fit into a single texture on `device`
+    fn load_texture(device: &wgpu::Device, path: &Path) -> anyhow::Result<image::RgbaImage> {
+        use anyhow::Context;
+
+        let image = image::open(path)
+            .with_context(|| format!("failed to decode texture {}", path.display()))?
+            .into_rgba8();
+        let max = device.limits().max_texture_dimension_2d;
+        if image.width() > max || image.height() > max {
+            Err(anyhow::anyhow!(
+                "texture {} is {}x{}, the device supports at most {max}x{max}",
+                path.display(),
+                image.width(),
+                image.height(),
+            ))?;
+        }
+        Ok(image)
+    }
+
+    /// uploads `image` with a full mip chain, downsampled on the cpu, so the quads don't
+    /// flicker when they're drawn smaller than the texture
+    fn upload_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::RgbaImage,
+    ) -> wgpu::TextureView {
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = size.max_mips(wgpu::TextureDimension::D2);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("quad texture"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let mut level = image.clone();
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                // each level is half the size of the previous one, but at least 1 pixel. the
+                // filter averages the srgb values, which is close enough for a preview
+                level = image::imageops::resize(
+                    &level,
+                    (level.width() / 2).max(1),
+                    (level.height() / 2).max(1),
+                    image::imageops::FilterType::Triangle,
+                );
+            }
+            // unlike buffer copies, `write_texture` takes tightly packed rows of any width
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    mip_level,
+                    ..texture.as_image_copy()
+                },
+                level.as_raw(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * level.width()),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width: level.width(),
+                    height: level.height(),
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(
+        context: &SceneContext,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        model_buffer: &wgpu::Buffer,
+        frame_tint_buffer: Option<&wgpu::Buffer>,
+    ) -> wgpu::BindGroup {
+        let device = context.device;
+        // anisotropy needs all three filters to be linear
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("quad sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp: context.anisotropy,
+            ..Default::default()
+        });
+
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: model_buffer.as_entire_binding(),
+            },
+        ];
+        if let Some(frame_tint_buffer) = frame_tint_buffer {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 3,
+                resource: frame_tint_buffer.as_entire_binding(),
+            });
+        }
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("quad bind group"),
+            layout,
+            entries: &entries,
+        })
+    }
+}
+
+impl Scene for QuadScene {
+    fn update(&mut self, dt: f32) {
+        // scaled by the real frame time so the rotation speed doesn't depend on the framerate
+        self.angle = (self.angle + ROTATION_SPEED * dt) % std::f32::consts::TAU;
+
+        // every instance gets its own grid cell
+        let scale = 0.5 / self.grid_size as f32;
+        let texture_aspect = self.texture_aspect.unwrap_or(1.0);
+        let transform = Mat4::from_scale(Vec3::splat(scale))
+            * Mat4::from_rotation_z(self.angle)
+            * Mat4::from_scale(Vec3::new(texture_aspect, 1.0, 1.0));
+        let model = Model {
+            transform: transform.to_cols_array(),
+            // a loaded texture or the pattern is shown in its own colors
+            tint: if self.texture_aspect.is_some() {
+                0.0
+            } else {
+                1.0
+            },
+            _pad: [0.0; 3],
+        };
+        self.queue
+            .write_buffer(&self.model_buffer, 0, bytemuck::bytes_of(&model));
+
+        self.time += dt;
+        // dims everything a little and back every four seconds
+        let pulse = 0.85 + 0.15 * (self.time * std::f32::consts::TAU / 4.0).cos();
+        self.frame_tint = [pulse, pulse, pulse, 1.0];
+        if let Some(frame_tint_buffer) = self.frame_tint_buffer.as_ref() {
+            self.queue
+                .write_buffer(frame_tint_buffer, 0, bytemuck::bytes_of(&self.frame_tint));
+        }
+        if let Some(pattern) = self.pattern.as_ref() {
+            pattern.update(&self.queue, self.time);
+        }
+    }
+
+    fn encode(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(pattern) = self.pattern.as_ref() {
+            pattern.encode(encoder);
+        }
+    }
+
+    fn render(&mut self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_pipeline(&self.pipeline);
+        // the quads replace the stencil with this, the outline compares against it
+        rpass.set_stencil_reference(OUTLINE_STENCIL);
+        rpass.set_bind_group(1, &self.bind_group, &[]);
+        if self.frame_tint_buffer.is_none() {
+            rpass.set_push_constants(
+                wgpu::ShaderStages::FRAGMENT,
+                0,
+                bytemuck::bytes_of(&self.frame_tint),
+            );
+        }
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        rpass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        rpass.draw_indexed(0..self.index_count, 0, 0..self.instance_count);
+
+        if let Some(outline_pipeline) = self.outline_pipeline.as_ref() {
+            rpass.set_pipeline(outline_pipeline);
+            rpass.draw_indexed(0..self.index_count, 0, 0..self.instance_count);
+        }
+    }
+
+    fn reload_shader(&mut self, context: &SceneContext, source: &str) -> anyhow::Result<()> {
+        (self.pipeline, self.outline_pipeline) = Self::create_pipelines(
+            context,
+            &self.pipeline_layout,
+            source,
+            self.outline_pipeline.is_some(),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use winit::dpi::PhysicalSize;
+
+/// what a scene needs to create its pipelines and buffers
+pub struct SceneContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a Arc<wgpu::Queue>,
+    /// of the color attachment the scene is rendered into
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    /// topology, culling and polygon mode, strips also need the `strip_index_format` of the
+    /// scene's index buffer
+    pub primitive: wgpu::PrimitiveState,
+    /// of the color target, set when the scene is drawn translucent. blended geometry
+    /// shouldn't write depth, or it hides what's behind it instead of letting it show through
+    pub blend: Option<wgpu::BlendState>,
+    /// of the second color target, which the fragment shaders write debug output to
+    pub debug_format: wgpu::TextureFormat,
+    /// for `anisotropy_clamp` of texture samplers, 1 turns it off
+    pub anisotropy: u16,
+    /// the device has `PUSH_CONSTANTS` with room for `PUSH_CONSTANT_SIZE` bytes, without them
+    /// scenes put those values into uniform buffers
+    pub push_constants: bool,
+    /// the camera in group 0 projects pixels from the top left corner of each viewport
+    /// instead of world space
+    pub pixel_space: bool,
+    /// of the scene's render targets, `Scene::resize` is called when it changes
+    pub size: PhysicalSize<u32>,
+    /// group 0 of every pipeline, `Gfx` binds the globals and the camera there
+    pub frame_bind_group_layout: &'a wgpu::BindGroupLayout,
+}
+
+impl SceneContext<'_> {
+    /// `primitive` for a pipeline whose draws use an index buffer of `index_format`, `None`
+    /// if they aren't indexed. indexed strips get the buffer's format, which wgpu checks
+    /// against every `set_index_buffer`, lists and unindexed draws may not have one
+    pub fn primitive_state(&self, index_format: Option<wgpu::IndexFormat>) -> wgpu::PrimitiveState {
+        wgpu::PrimitiveState {
+            strip_index_format: index_format.filter(|_| self.primitive.topology.is_strip()),
+            ..self.primitive
+        }
+    }
+}
+
+/// whatever `Gfx` draws, it sets up the render pass around it
+pub trait Scene {
+    /// advances the animation by `dt` seconds
+    fn update(&mut self, dt: f32);
+
+    /// records whatever has to run before the render pass, like compute passes
+    fn encode(&mut self, _encoder: &mut wgpu::CommandEncoder) {}
+
+    /// records the draw calls, group 0 is already bound
+    fn render(&mut self, rpass: &mut wgpu::RenderPass);
+
+    /// the render targets were resized to `context.size`
+    fn resize(&mut self, _context: &SceneContext) {}
+
+    /// rebuilds the pipelines from new shader source for `--watch`. `Gfx` catches the
+    /// validation errors as well as the returned ones and stops drawing the scene until a
+    /// reload succeeds
+    fn reload_shader(&mut self, _context: &SceneContext, _source: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// draws nothing, only the clear color shows
+pub struct EmptyScene;
+
+impl Scene for EmptyScene {
+    fn update(&mut self, _dt: f32) {}
+
+    fn render(&mut self, _rpass: &mut wgpu::RenderPass) {}
+}
@@ -1,11 +1,122 @@
+struct Globals {
+    // framebuffer size in pixels
+    resolution: vec2<f32>,
+    // cursor position in [0, 1], from the top left corner
+    mouse: vec2<f32>,
+    // seconds of animation, stands still while paused
+    time: f32,
+    // physical pixels per logical pixel of the window, 1 when headless
+    scale_factor: f32,
+    // alpha of the quads, 1 unless blending them over the clear color
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+@group(0) @binding(1)
+var<uniform> view_projection: mat4x4<f32>;
+
+struct Model {
+    // applied to every instance before its offset
+    transform: mat4x4<f32>,
+    // how much the animated vertex color tints the texture
+    tint: f32,
+}
+
+@group(1) @binding(0)
+var texture: texture_2d<f32>;
+
+@group(1) @binding(1)
+var texture_sampler: sampler;
+
+@group(1) @binding(2)
+var<uniform> model: Model;
+
+// multiplied onto every pixel, pushed every frame. without push constants the scene replaces
+// this line with a uniform binding
+var<push_constant> frame_tint: vec4<f32>;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+}
+
+struct InstanceInput {
+    // in world space, the grid spans [-1, 1] on the xy plane
+    @location(2) offset: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+// clip space position of a vertex of the quad, scaled around its center first
+fn project(position: vec3<f32>, scale: f32, instance: InstanceInput) -> vec4<f32> {
+    let world = model.transform * vec4<f32>(position * scale, 1.0) + vec4<f32>(instance.offset, 0.0, 0.0);
+    return view_projection * world;
+}
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = project(in.position, 1.0, instance);
+    out.color = in.color;
+    out.uv = in.uv;
+    return out;
+}
+
+// rotates the hue of a color by `angle` radians around the gray axis
+fn hue_rotate(color: vec3<f32>, angle: f32) -> vec3<f32> {
+    let k = vec3<f32>(0.57735);
+    let c = cos(angle);
+    return color * c + cross(k, color) * sin(angle) + k * dot(k, color) * (1.0 - c);
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // shown by the debug post-processing effect
+    @location(1) debug: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    // one full cycle through the hues every ten seconds
+    let angle = globals.time * 0.1 * 6.2831855;
+
+    // the fragment position is in framebuffer pixels from the top left, like the mouse,
+    // scaling x by the aspect ratio keeps the spotlight around the cursor round
+    let uv = in.position.xy / globals.resolution;
+    let aspect = vec2<f32>(globals.resolution.x / globals.resolution.y, 1.0);
+    let spotlight = 1.0 - smoothstep(0.0, 0.6, distance(uv * aspect, globals.mouse * aspect));
+    let brightness = mix(0.3, 1.0, spotlight);
+
+    let tint = mix(vec3<f32>(1.0), hue_rotate(in.color, angle), model.tint);
+    let texel = textureSample(texture, texture_sampler, in.uv);
+    let color = texel.rgb * tint;
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(color * brightness, texel.a * globals.opacity) * frame_tint;
+    out.debug = vec4<f32>(in.uv, 0.0, 1.0);
+    return out;
+}
+
+// how much larger the outline is than the quad, only the part sticking out is drawn
+const OUTLINE_SCALE: f32 = 1.06;
+const OUTLINE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.8, 0.0);
+
 @vertex
-fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4<f32> {
-    let x = f32(i32(in_vertex_index) - 1);
-    let y = f32(i32(in_vertex_index & 1u) * 2 - 1);
-    return vec4<f32>(x, y, 0.0, 1.0);
+fn vs_outline(in: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+    return project(in.position, OUTLINE_SCALE, instance);
 }
 
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+fn fs_outline() -> FragmentOutput {
+    var out: FragmentOutput;
+    out.color = vec4<f32>(OUTLINE_COLOR, globals.opacity);
+    out.debug = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    return out;
 }
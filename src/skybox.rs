@@ -0,0 +1,194 @@
+use std::{borrow::Cow, path::Path};
+
+use anyhow::Context;
+
+use crate::gfx::{DEBUG_FORMAT, DEPTH_FORMAT};
+
+/// the files in the `--skybox` directory, in the order of the cube map's layers
+const FACES: [&str; 6] = ["px", "nx", "py", "ny", "pz", "nz"];
+
+/// a cube map drawn behind the scene by a full-screen triangle, each pixel looks up the
+/// direction the camera sees it in. the scene pass still clears, the sky covers it
+pub struct Skybox {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Skybox {
+    /// loads `px.png`, `nx.png`, `py.png`, `ny.png`, `pz.png` and `nz.png` from `directory`,
+    /// which have to be square and all of the same size. `format` and `sample_count` are the
+    /// scene's color target's, group 0 is the scene's too for the camera
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        frame_bind_group_layout: &wgpu::BindGroupLayout,
+        directory: &Path,
+    ) -> anyhow::Result<Self> {
+        let faces = FACES
+            .iter()
+            .map(|face| {
+                let path = directory.join(face).with_extension("png");
+                image::open(&path)
+                    .with_context(|| format!("failed to decode skybox face {}", path.display()))
+                    .map(|image| (path, image.into_rgba8()))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let edge = faces[0].1.width();
+        let max = device.limits().max_texture_dimension_2d;
+        for (path, image) in &faces {
+            if image.width() != edge || image.height() != edge {
+                Err(anyhow::anyhow!(
+                    "skybox face {} is {}x{}, all of them have to be {edge}x{edge}",
+                    path.display(),
+                    image.width(),
+                    image.height(),
+                ))?;
+            }
+        }
+        if edge > max {
+            Err(anyhow::anyhow!(
+                "the skybox faces are {edge}x{edge}, the device supports at most {max}x{max}"
+            ))?;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("skybox texture"),
+            size: wgpu::Extent3d {
+                width: edge,
+                height: edge,
+                depth_or_array_layers: FACES.len() as u32,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (layer, (_, image)) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    origin: wgpu::Origin3d {
+                        z: layer as u32,
+                        ..wgpu::Origin3d::ZERO
+                    },
+                    ..texture.as_image_copy()
+                },
+                image.as_raw(),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * edge),
+                    rows_per_image: Some(edge),
+                },
+                wgpu::Extent3d {
+                    width: edge,
+                    height: edge,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        // the six layers of a 2d array texture, sampled by direction
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("skybox view"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("skybox sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("skybox bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("skybox bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("skybox pipeline layout"),
+            bind_group_layouts: &[frame_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("skybox shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("skybox.wgsl"))),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("skybox pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into()), Some(DEBUG_FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // drawn first and infinitely far away, the scene covers it wherever it is
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            pipeline,
+            bind_group,
+        })
+    }
+
+    /// before the scene, with group 0 bound to the camera of the viewport it fills
+    pub fn render(&self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(1, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,57 @@
+// matches `CameraUniform` in gfx.rs, the other shaders only declare its first matrix
+struct Camera {
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(1)
+var<uniform> camera: Camera;
+
+@group(1) @binding(0)
+var sky: texture_cube<f32>;
+
+@group(1) @binding(1)
+var sky_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // of the viewport, interpolates linearly unlike the view direction
+    @location(0) ndc: vec2<f32>,
+}
+
+// a single triangle that covers the whole viewport, like the one in blit.wgsl
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.ndc = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    out.position = vec4<f32>(out.ndc, 0.0, 1.0);
+    return out;
+}
+
+// where the point at `ndc` and `depth` is in world space
+fn unproject(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+    let world = camera.inverse_view_projection * vec4<f32>(ndc, depth, 1.0);
+    return world.xyz / world.w;
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // shown by the debug post-processing effect
+    @location(1) debug: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    // from the near plane to the far plane through this pixel
+    let direction = normalize(unproject(in.ndc, 1.0) - unproject(in.ndc, 0.0));
+
+    // cube maps are left handed, flipping z puts the +z face in front of the default camera,
+    // which looks down -z, without mirroring it
+    let texel = textureSample(sky, sky_sampler, direction * vec3<f32>(1.0, 1.0, -1.0));
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(texel.rgb, 1.0);
+    out.debug = vec4<f32>(direction * 0.5 + 0.5, 1.0);
+    return out;
+}
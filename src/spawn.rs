@@ -0,0 +1,372 @@
+use std::borrow::Cow;
+
+use anyhow::Context;
+use glam::Vec2;
+
+use crate::{
+    gfx::{DEBUG_FORMAT, DEPTH_FORMAT},
+    json,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    /// in normalized device coordinates
+    position: [f32; 2],
+    color: [f32; 3],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x3];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// from the center to each corner, in normalized device coordinates of the scene's height
+const RADIUS: f32 = 0.05;
+/// of the first buffer, it doubles whenever it's full
+const INITIAL_CAPACITY: usize = 3 * 16;
+
+/// where the spawned triangles are saved with ctrl+s and loaded from with ctrl+o, as
+///
+/// ```json
+/// {"vertices": [
+///     {"position": [-0.5, 0.25], "color": [1, 0, 0]},
+///     ...
+/// ]}
+/// ```
+///
+/// with three vertices per triangle
+pub const SCENE_PATH: &str = "scene.json";
+
+/// small triangles added by clicking into the scene, drawn on top of it
+pub struct Spawned {
+    pipeline: wgpu::RenderPipeline,
+    /// everything that's drawn, the buffer holds a copy of it
+    vertices: Vec<Vertex>,
+    buffer: wgpu::Buffer,
+}
+
+impl Spawned {
+    /// `format` and `sample_count` are the scene's color target's
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("spawned pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("spawned shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("spawn.wgsl"))),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("spawned pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into()), Some(DEBUG_FORMAT.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // in front of whatever the scene drew there
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            vertices: Vec::new(),
+            buffer: Self::create_buffer(device, INITIAL_CAPACITY),
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("spawned vertices"),
+            size: (capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// adds a triangle around `center`, in normalized device coordinates. `aspect` is the
+    /// scene's width / height, which keeps the triangle from being stretched
+    pub fn push(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, center: Vec2, aspect: f32) {
+        let start = self.vertices.len();
+        let colors = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        self.vertices
+            .extend(colors.iter().enumerate().map(|(i, color)| {
+                // counter-clockwise from the top
+                let angle = std::f32::consts::FRAC_PI_2 + i as f32 * std::f32::consts::TAU / 3.0;
+                let corner = Vec2::from_angle(angle) * RADIUS;
+                Vertex {
+                    position: (center + Vec2::new(corner.x / aspect, corner.y)).into(),
+                    color: *color,
+                }
+            }));
+
+        let capacity = self.buffer.size() as usize / std::mem::size_of::<Vertex>();
+        if self.vertices.len() > capacity {
+            // the old buffer can't be resized, everything goes into one twice as big
+            self.buffer = Self::create_buffer(device, capacity * 2);
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.vertices));
+        } else {
+            queue.write_buffer(
+                &self.buffer,
+                (start * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+                bytemuck::cast_slice(&self.vertices[start..]),
+            );
+        }
+        log::info!("spawned triangle {}", self.vertices.len() / 3);
+    }
+
+    /// removes the last triangle, if there is one. what's left in the buffer behind the
+    /// remaining ones isn't drawn, so nothing has to be uploaded
+    pub fn pop(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        self.vertices.truncate(self.vertices.len() - 3);
+        log::info!("{} spawned triangles left", self.vertices.len() / 3);
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::write(SCENE_PATH, Self::serialize(&self.vertices))
+            .with_context(|| format!("failed to write {SCENE_PATH}"))?;
+        log::info!(
+            "saved {} triangles to {SCENE_PATH}",
+            self.vertices.len() / 3
+        );
+        Ok(())
+    }
+
+    /// replaces the triangles with the saved ones, they're kept if the file can't be read
+    pub fn load(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> anyhow::Result<()> {
+        let source = std::fs::read_to_string(SCENE_PATH)
+            .with_context(|| format!("failed to read {SCENE_PATH}"))?;
+        self.vertices =
+            Self::parse(&source).with_context(|| format!("failed to load {SCENE_PATH}"))?;
+
+        let capacity = self.buffer.size() as usize / std::mem::size_of::<Vertex>();
+        if self.vertices.len() > capacity {
+            let capacity = self
+                .vertices
+                .len()
+                .next_power_of_two()
+                .max(INITIAL_CAPACITY);
+            self.buffer = Self::create_buffer(device, capacity);
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.vertices));
+        log::info!(
+            "loaded {} triangles from {SCENE_PATH}",
+            self.vertices.len() / 3
+        );
+        Ok(())
+    }
+
+    /// one vertex per line, readable but still short
+    fn serialize(vertices: &[Vertex]) -> String {
+        if vertices.is_empty() {
+            return "{\"vertices\": []}\n".into();
+        }
+        let lines = vertices
+            .iter()
+            .map(|vertex| {
+                let vertex = json::Value::Object(vec![
+                    ("position".into(), vertex.position[..].into()),
+                    ("color".into(), vertex.color[..].into()),
+                ]);
+                format!("    {vertex}")
+            })
+            .collect::<Vec<_>>();
+        format!("{{\"vertices\": [\n{}\n]}}\n", lines.join(",\n"))
+    }
+
+    fn parse(source: &str) -> anyhow::Result<Vec<Vertex>> {
+        let document = json::Value::parse(source)?;
+        let json::Value::Object(members) = &document else {
+            Err(anyhow::anyhow!("the scene has to be an object"))?
+        };
+        if let Some((key, _)) = members.iter().find(|(key, _)| key != "vertices") {
+            Err(anyhow::anyhow!("unknown key `{key}`"))?;
+        }
+        let Some(vertices) = document.get("vertices") else {
+            return Ok(Vec::new());
+        };
+        let vertices = vertices
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("`vertices` has to be an array of objects"))?;
+        if vertices.len() % 3 != 0 {
+            Err(anyhow::anyhow!(
+                "{} vertices don't make whole triangles",
+                vertices.len()
+            ))?;
+        }
+
+        fn numbers<const N: usize>(vertex: &json::Value, key: &str) -> anyhow::Result<[f32; N]> {
+            let error = || anyhow::anyhow!("`{key}` has to be an array of {N} numbers");
+            let numbers = vertex
+                .get(key)
+                .and_then(json::Value::as_array)
+                .ok_or_else(error)?
+                .iter()
+                .map(|value| value.as_f64().map(|value| value as f32).ok_or_else(error))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            numbers.try_into().map_err(|_| error())
+        }
+        vertices
+            .iter()
+            .enumerate()
+            .map(|(i, vertex)| {
+                let json::Value::Object(members) = vertex else {
+                    Err(anyhow::anyhow!("vertex {i} has to be an object"))?
+                };
+                if let Some((key, _)) = members
+                    .iter()
+                    .find(|(key, _)| key != "position" && key != "color")
+                {
+                    Err(anyhow::anyhow!("unknown key `{key}` in vertex {i}"))?;
+                }
+                Ok(Vertex {
+                    position: numbers(vertex, "position")
+                        .with_context(|| format!("invalid vertex {i}"))?,
+                    color: numbers(vertex, "color")
+                        .with_context(|| format!("invalid vertex {i}"))?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn render(&self, rpass: &mut wgpu::RenderPass) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_vertex_buffer(0, self.buffer.slice(..));
+        rpass.draw(0..self.vertices.len() as u32, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 2], color: [f32; 3]) -> Vertex {
+        Vertex { position, color }
+    }
+
+    #[test]
+    fn parses_whole_triangles() {
+        let vertices = Spawned::parse(
+            r#"{"vertices": [
+                {"position": [-0.5, 0.25], "color": [1, 0, 0]},
+                {"color": [0, 1, 0], "position": [0.5, 0.25]},
+                {"position": [0, -1], "color": [0, 0, 0.5]}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(vertices[0].position, [-0.5, 0.25]);
+        assert_eq!(vertices[0].color, [1.0, 0.0, 0.0]);
+        assert_eq!(vertices[1].position, [0.5, 0.25]);
+        assert_eq!(vertices[2].color, [0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn an_empty_scene_has_no_vertices() {
+        assert!(Spawned::parse("{}").unwrap().is_empty());
+        assert!(Spawned::parse(r#"{"vertices": []}"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reads_back_what_it_saved() {
+        let saved = [
+            vertex([-0.05, 0.1], [1.0, 0.0, 0.0]),
+            vertex([0.0, 0.05], [0.0, 1.0, 0.0]),
+            vertex([0.05, 0.1], [0.0, 0.0, 1.0]),
+        ];
+        let source = Spawned::serialize(&saved);
+        assert!(source.contains(r#"{"position": [-0.05, 0.1], "color": [1, 0, 0]}"#));
+
+        let loaded = Spawned::parse(&source).unwrap();
+        for (saved, loaded) in saved.iter().zip(&loaded) {
+            assert_eq!(saved.position, loaded.position);
+            assert_eq!(saved.color, loaded.color);
+        }
+        assert_eq!(Spawned::parse(&Spawned::serialize(&[])).unwrap().len(), 0);
+    }
+
+    /// a scene of one triangle with `vertex` in every corner
+    fn triangle(vertex: &str) -> String {
+        format!(r#"{{"vertices": [{vertex}, {vertex}, {vertex}]}}"#)
+    }
+
+    #[test]
+    fn rejects_invalid_scenes() {
+        // each of the broken ones below differs from this in one place
+        assert!(Spawned::parse(&triangle(r#"{"position": [0, 0], "color": [0, 0, 0]}"#)).is_ok());
+        for source in [
+            "[]".into(),
+            r#"{"vertices": {}}"#.into(),
+            r#"{"vertices": ["#.into(),
+            triangle("1"),
+            triangle(r#"{"position": [0, 0]}"#),
+            triangle(r#"{"position": [0], "color": [0, 0, 0]}"#),
+            triangle(r#"{"position": [0, "0"], "color": [0, 0, 0]}"#),
+            triangle(r#"{"position": [0, 0], "color": [0, 0, 0, 1]}"#),
+        ] {
+            assert!(Spawned::parse(&source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_incomplete_triangles() {
+        let error = Spawned::parse(
+            r#"{"vertices": [
+                {"position": [0, 0], "color": [0, 0, 0]},
+                {"position": [1, 0], "color": [0, 0, 0]}
+            ]}"#,
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().contains("2 vertices"), "{error}");
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let error = Spawned::parse(r#"{"vertex": []}"#).unwrap_err();
+        assert!(error.to_string().contains("`vertex`"), "{error}");
+
+        let error = Spawned::parse(&triangle(
+            r#"{"position": [0, 0], "color": [0, 0, 0], "size": 1}"#,
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("`size`"), "{error}");
+    }
+}
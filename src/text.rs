@@ -0,0 +1,407 @@
+use std::borrow::Cow;
+
+use glam::Vec2;
+use winit::dpi::PhysicalSize;
+
+/// of a glyph in font pixels, the cells in the atlas are just as big
+const GLYPH_SIZE: [u32; 2] = [5, 7];
+/// how far the next glyph and the next line start, in font pixels
+const ADVANCE: Vec2 = Vec2::new(6.0, 9.0);
+/// the printable ascii characters from ' ' on, anything else is drawn as '?'
+const FIRST_CHAR: char = ' ';
+/// columns of 7 bits each, the lowest is the top row. after the last character the atlas has
+/// a solid cell that rectangles are filled from
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31], [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00], [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x09, 0x01],
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41], [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31], [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x3f, 0x40, 0x38, 0x40, 0x3f], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x07, 0x08, 0x70, 0x08, 0x07], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00], [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+/// glyphs in the atlas, one more than there are characters for the solid cell
+const CELLS: u32 = GLYPHS.len() as u32 + 1;
+/// of the first vertex buffer, it doubles whenever it's too small
+const INITIAL_CAPACITY: usize = 6 * 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    /// in physical pixels from the top left corner
+    position: [f32; 2],
+    uv: [f32; 2],
+    /// linear, the alpha is multiplied by the glyph's coverage
+    color: [f32; 4],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// matches `Screen` in text.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Screen {
+    /// of the target in physical pixels
+    size: [f32; 2],
+    _pad: [f32; 2],
+}
+
+impl From<PhysicalSize<u32>> for Screen {
+    fn from(size: PhysicalSize<u32>) -> Self {
+        Self {
+            size: [size.width as f32, size.height as f32],
+            _pad: [0.0; 2],
+        }
+    }
+}
+
+/// text in a built-in 5x7 pixel font and solid rectangles, drawn over whatever is in the
+/// target in a pass of their own. everything queued is drawn once by the next `encode`
+pub struct TextRenderer {
+    pipeline: wgpu::RenderPipeline,
+    screen_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// queued since the last `encode`
+    vertices: Vec<Vertex>,
+    buffer: wgpu::Buffer,
+}
+
+impl TextRenderer {
+    /// `format` and `size` are the target's, it's drawn after the scene is resolved so it's
+    /// never multisampled
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+    ) -> Self {
+        let [glyph_width, glyph_height] = GLYPH_SIZE;
+        let atlas_width = CELLS * glyph_width;
+        let mut atlas = vec![0u8; (atlas_width * glyph_height) as usize];
+        let columns = GLYPHS
+            .iter()
+            .flatten()
+            .chain(&[0x7f; GLYPH_SIZE[0] as usize]);
+        for (x, column) in columns.enumerate() {
+            for y in 0..glyph_height as usize {
+                if column >> y & 1 != 0 {
+                    atlas[y * atlas_width as usize + x] = 255;
+                }
+            }
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("font atlas"),
+            size: wgpu::Extent3d {
+                width: atlas_width,
+                height: glyph_height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            &atlas,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(atlas_width),
+                rows_per_image: None,
+            },
+            texture.size(),
+        );
+        // the font is scaled by whole pixels, nearest filtering keeps it sharp
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("font sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let screen_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text screen"),
+            size: std::mem::size_of::<Screen>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&screen_buffer, 0, bytemuck::bytes_of(&Screen::from(size)));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("text shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("text.wgsl"))),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("text pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            screen_buffer,
+            bind_group,
+            vertices: Vec::new(),
+            buffer: Self::create_buffer(device, INITIAL_CAPACITY),
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text vertices"),
+            size: (capacity * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// the target's new size, the text keeps its size in pixels
+    pub fn resize(&self, queue: &wgpu::Queue, size: PhysicalSize<u32>) {
+        queue.write_buffer(
+            &self.screen_buffer,
+            0,
+            bytemuck::bytes_of(&Screen::from(size)),
+        );
+    }
+
+    /// how much room `text` takes up at `scale`, see `TextRenderer::text`
+    pub fn size(text: &str, scale: f32) -> Vec2 {
+        let lines = text.lines().count();
+        let columns = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return Vec2::ZERO;
+        }
+        // the gap after the last glyph and below the last line isn't part of it
+        let cells = Vec2::new(columns as f32, lines as f32);
+        let gap = ADVANCE - Vec2::new(GLYPH_SIZE[0] as f32, GLYPH_SIZE[1] as f32);
+        (cells * ADVANCE - gap) * scale
+    }
+
+    /// queues `text` with its top left corner at `position`, in physical pixels. every pixel
+    /// of the font is `scale` pixels big, `\n` starts a new line
+    pub fn text(&mut self, position: Vec2, scale: f32, color: [f32; 4], text: &str) {
+        let glyph = Vec2::new(GLYPH_SIZE[0] as f32, GLYPH_SIZE[1] as f32) * scale;
+        for (row, line) in text.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let index = match (c as u32).checked_sub(FIRST_CHAR as u32) {
+                    Some(index) if (index as usize) < GLYPHS.len() => index,
+                    _ => '?' as u32 - FIRST_CHAR as u32,
+                };
+                // spaces are empty, there's no need to draw them
+                if index == 0 {
+                    continue;
+                }
+                let min = position + Vec2::new(column as f32, row as f32) * ADVANCE * scale;
+                let u = index as f32 / CELLS as f32;
+                self.quad(
+                    min,
+                    glyph,
+                    [Vec2::new(u, 0.0), Vec2::new(u + 1.0 / CELLS as f32, 1.0)],
+                    color,
+                );
+            }
+        }
+    }
+
+    /// queues a filled rectangle, in physical pixels
+    pub fn rect(&mut self, min: Vec2, size: Vec2, color: [f32; 4]) {
+        // the middle of the solid cell, every pixel of the rectangle samples it
+        let uv = Vec2::new((CELLS as f32 - 0.5) / CELLS as f32, 0.5);
+        self.quad(min, size, [uv, uv], color);
+    }
+
+    fn quad(&mut self, min: Vec2, size: Vec2, [uv_min, uv_max]: [Vec2; 2], color: [f32; 4]) {
+        let max = min + size;
+        let corner = |x: bool, y: bool| Vertex {
+            position: [if x { max.x } else { min.x }, if y { max.y } else { min.y }],
+            uv: [
+                if x { uv_max.x } else { uv_min.x },
+                if y { uv_max.y } else { uv_min.y },
+            ],
+            color,
+        };
+        // two triangles, their winding doesn't matter without culling
+        self.vertices.extend([
+            corner(false, false),
+            corner(false, true),
+            corner(true, false),
+            corner(true, false),
+            corner(false, true),
+            corner(true, true),
+        ]);
+    }
+
+    /// draws everything queued since the last call into `view`, on top of what's in it
+    pub fn encode(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let capacity = self.buffer.size() as usize / std::mem::size_of::<Vertex>();
+        if self.vertices.len() > capacity {
+            let capacity = self.vertices.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, capacity);
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.vertices));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("text pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.buffer.slice(..));
+        rpass.draw(0..self.vertices.len() as u32, 0..1);
+        drop(rpass);
+
+        self.vertices.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_without_the_trailing_gaps() {
+        assert_eq!(TextRenderer::size("A", 1.0), Vec2::new(5.0, 7.0));
+        assert_eq!(TextRenderer::size("60 FPS", 2.0), Vec2::new(70.0, 14.0));
+        // the longest line is as wide as the text
+        assert_eq!(TextRenderer::size("ab\nabc", 1.0), Vec2::new(17.0, 16.0));
+        assert_eq!(TextRenderer::size("", 1.0), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,43 @@
+// matches `Screen` in text.rs
+struct Screen {
+    size: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> screen: Screen;
+
+// one 5x7 cell per glyph, 1 where it's covered
+@group(0) @binding(1)
+var font: texture_2d<f32>;
+
+@group(0) @binding(2)
+var font_sampler: sampler;
+
+struct VertexInput {
+    // in pixels from the top left corner
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let ndc = in.position / screen.size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0);
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.uv = in.uv;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(font, font_sampler, in.uv).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
@@ -0,0 +1,193 @@
+use std::{fmt, sync::mpsc, time::Duration};
+
+use web_time::Instant;
+
+/// how often the average gpu time is logged
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// measures how long the gpu spends in the scene's render pass with a pair of timestamps
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// nanoseconds per timestamp tick
+    period: f32,
+    /// set while `readback_buffer` is mapped or waiting to be, no timestamps are written until
+    /// it's read
+    mapped: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    total_ms: f32,
+    samples: u32,
+    last_report: Instant,
+}
+
+impl GpuTimer {
+    /// the device needs `Features::TIMESTAMP_QUERY`
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let size = 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            mapped: None,
+            total_ms: 0.0,
+            samples: 0,
+            last_report: Instant::now(),
+        }
+    }
+
+    /// `None` while the last measurement is still being read back
+    pub fn timestamp_writes(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.mapped
+            .is_none()
+            .then_some(wgpu::RenderPassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(0),
+                end_of_pass_write_index: Some(1),
+            })
+    }
+
+    /// has to be recorded after the pass that used `timestamp_writes`
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if self.mapped.is_some() {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.readback_buffer.size(),
+        );
+    }
+
+    /// has to be called after the commands from `resolve` were submitted
+    pub fn start_readback(&mut self) {
+        if self.mapped.is_some() {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.mapped = Some(receiver);
+    }
+
+    /// picks up a finished readback without blocking, the device has to be polled beforehand
+    pub fn poll(&mut self) {
+        let Some(result) = self
+            .mapped
+            .as_ref()
+            .and_then(|mapped| mapped.try_recv().ok())
+        else {
+            return;
+        };
+        self.mapped = None;
+
+        if let Err(err) = result {
+            log::error!("failed to map the timestamp buffer: {err}");
+            return;
+        }
+
+        let (start, end) = {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            (timestamps[0], timestamps[1])
+        };
+        self.readback_buffer.unmap();
+
+        // some backends report timestamps that aren't monotonic across a pass
+        let ticks = end.saturating_sub(start);
+        self.total_ms += ticks as f32 * self.period / 1_000_000.0;
+        self.samples += 1;
+
+        if self.last_report.elapsed() >= REPORT_INTERVAL {
+            log::info!(
+                "gpu frame time {:.3} ms, averaged over {} frames",
+                self.total_ms / self.samples as f32,
+                self.samples,
+            );
+            self.total_ms = 0.0;
+            self.samples = 0;
+            self.last_report = Instant::now();
+        }
+    }
+}
+
+/// cpu frame times for `--bench`, the first `warmup` frames aren't part of the statistics
+pub struct Benchmark {
+    frames: u32,
+    warmup: u32,
+    rendered: u32,
+    times: Vec<Duration>,
+}
+
+impl Benchmark {
+    /// renders `frames` in total, `warmup` has to be less than that
+    pub fn new(frames: u32, warmup: u32) -> Self {
+        Self {
+            frames,
+            warmup,
+            rendered: 0,
+            times: Vec::with_capacity(frames.saturating_sub(warmup) as usize),
+        }
+    }
+
+    /// records a frame that took `time`, true once all frames are rendered
+    pub fn frame(&mut self, time: Duration) -> bool {
+        self.rendered += 1;
+        if self.rendered > self.warmup {
+            self.times.push(time);
+        }
+        self.rendered >= self.frames
+    }
+}
+
+impl fmt::Display for Benchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut times = self
+            .times
+            .iter()
+            .map(|time| time.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        if times.is_empty() {
+            return write!(f, "no frames measured");
+        }
+        times.sort_by(f64::total_cmp);
+
+        let mean = times.iter().sum::<f64>() / times.len() as f64;
+        // the frame time 99% of the frames are at or below
+        let p99 = times[(times.len() as f64 * 0.99).ceil() as usize - 1];
+        write!(
+            f,
+            "{} frames ({} warmup not counted): min {:.3} ms, max {:.3} ms, mean {:.3} ms, p99 {:.3} ms",
+            times.len(),
+            self.warmup,
+            times[0],
+            times[times.len() - 1],
+            mean,
+            p99,
+        )
+    }
+}
@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+use wgpu::util::DeviceExt;
+
+use crate::{
+    gfx::DEPTH_FORMAT,
+    scene::{Scene, SceneContext},
+};
+
+/// the triangle's shader, used by `--watch`
+pub const SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/triangle.wgsl");
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// an equilateral triangle, counter-clockwise with its centroid at the origin, which the
+/// default camera puts in the middle of the frame
+const VERTICES: &[Vertex] = &[
+    Vertex {
+        position: [0.0, 1.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [-0.8660254, -0.5, 0.0],
+        color: [0.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [0.8660254, -0.5, 0.0],
+        color: [0.0, 0.0, 1.0],
+    },
+];
+
+/// with `pixel_space`, in pixels from the top left corner. counter-clockwise on the screen
+/// too, `camera::pixel_projection` flips y
+const PIXEL_VERTICES: &[Vertex] = &[
+    Vertex {
+        position: [200.0, 100.0, 0.0],
+        color: [1.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [100.0, 300.0, 0.0],
+        color: [0.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [300.0, 300.0, 0.0],
+        color: [0.0, 0.0, 1.0],
+    },
+];
+
+/// how far each 8 bit channel may be off, the middle pixel's center isn't exactly on the
+/// centroid
+#[cfg(not(target_arch = "wasm32"))]
+const TOLERANCE: u8 = 2;
+
+/// the classic hello triangle, with a red, a green and a blue corner
+pub struct TriangleScene {
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+}
+
+impl TriangleScene {
+    pub fn new(context: &SceneContext) -> Self {
+        let device = context.device;
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("triangle vertices"),
+            contents: bytemuck::cast_slice(match context.pixel_space {
+                true => PIXEL_VERTICES,
+                false => VERTICES,
+            }),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("triangle pipeline layout"),
+            bind_group_layouts: &[context.frame_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline =
+            Self::create_pipeline(context, &pipeline_layout, include_str!("triangle.wgsl"));
+
+        Self {
+            pipeline_layout,
+            pipeline,
+            vertex_buffer,
+        }
+    }
+
+    fn create_pipeline(
+        context: &SceneContext,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let device = context.device;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("triangle shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("triangle pipeline"),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: context.format,
+                        blend: context.blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(context.debug_format.into()),
+                ],
+            }),
+            // a single triangle isn't indexed, strips don't need an index format
+            primitive: context.primitive_state(None),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: context.blend.is_none(),
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: context.sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// fails unless `rgba`, read from the middle of a frame rendered into a target that's
+    /// `srgb` encoded or not, is the average of the corners' colors. that's what the rasterizer
+    /// interpolates at the centroid. only headless frames are checked
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_interpolation(rgba: [u8; 4], srgb: bool) -> anyhow::Result<()> {
+        let expected = Self::expected_center(srgb);
+        let [r, g, b, _] = rgba;
+        if [r, g, b]
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) <= TOLERANCE)
+        {
+            log::info!("the middle of the triangle is {:?}, as expected", [r, g, b]);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "the middle of the triangle is {:?}, expected {expected:?}",
+                [r, g, b]
+            ))
+        }
+    }
+
+    /// the average of the corners' colors as 8 bit rgb, encoded like the target
+    #[cfg(not(target_arch = "wasm32"))]
+    fn expected_center(srgb: bool) -> [u8; 3] {
+        [0, 1, 2].map(|channel| {
+            let linear = VERTICES
+                .iter()
+                .map(|vertex| vertex.color[channel])
+                .sum::<f32>()
+                / VERTICES.len() as f32;
+            let encoded = match srgb {
+                true if linear <= 0.0031308 => linear * 12.92,
+                true => 1.055 * linear.powf(1.0 / 2.4) - 0.055,
+                false => linear,
+            };
+            (encoded * 255.0).round() as u8
+        })
+    }
+}
+
+impl Scene for TriangleScene {
+    fn update(&mut self, _dt: f32) {}
+
+    fn render(&mut self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        // both have three
+        rpass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+
+    fn reload_shader(&mut self, context: &SceneContext, source: &str) -> anyhow::Result<()> {
+        self.pipeline = Self::create_pipeline(context, &self.pipeline_layout, source);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::gfx::{GfxBuilder, DEFAULT_SIZE};
+
+    #[test]
+    fn expects_the_average_of_the_corners() {
+        // a third of each channel, 1 / 3 encodes to 156 / 255 in srgb
+        assert_eq!(TriangleScene::expected_center(false), [85, 85, 85]);
+        assert_eq!(TriangleScene::expected_center(true), [156, 156, 156]);
+    }
+
+    #[test]
+    fn checks_the_center_within_the_tolerance() {
+        assert!(TriangleScene::check_interpolation([85, 85, 85, 255], false).is_ok());
+        assert!(TriangleScene::check_interpolation([87, 83, 86, 255], false).is_ok());
+        assert!(TriangleScene::check_interpolation([88, 85, 85, 255], false).is_err());
+        assert!(TriangleScene::check_interpolation([85, 85, 85, 255], true).is_err());
+        assert!(TriangleScene::check_interpolation([255, 0, 0, 255], true).is_err());
+    }
+
+    /// needs an adapter, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn interpolates_the_vertex_colors() {
+        let mut gfx = pollster::block_on(GfxBuilder::default().build_headless(DEFAULT_SIZE))
+            .expect("no adapter to render with");
+        let scene = TriangleScene::new(&gfx.scene_context());
+        gfx.set_scene(Box::new(scene));
+        gfx.update(0.0);
+
+        let pixel = gfx
+            .read_pixel([DEFAULT_SIZE.width / 2, DEFAULT_SIZE.height / 2])
+            .unwrap();
+        TriangleScene::check_interpolation(pixel, true).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+// matches `Globals` in shader.wgsl
+struct Globals {
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    time: f32,
+    scale_factor: f32,
+    // alpha of the triangle, 1 unless blending it
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+@group(0) @binding(1)
+var<uniform> view_projection: mat4x4<f32>;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // interpolated between the corners by the rasterizer
+    @location(0) color: vec3<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = view_projection * vec4<f32>(in.position, 1.0);
+    out.color = in.color;
+    return out;
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // shown by the debug post-processing effect
+    @location(1) debug: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var out: FragmentOutput;
+    out.color = vec4<f32>(in.color, globals.opacity);
+    out.debug = vec4<f32>(in.color, 1.0);
+    return out;
+}
@@ -0,0 +1,46 @@
+//! glue for running in a browser:
+//!
+//! ```sh
+//! cargo build --release --target wasm32-unknown-unknown
+//! wasm-bindgen --target web --out-dir web target/wasm32-unknown-unknown/release/rust-wgpu.wasm
+//! ```
+//!
+//! and a page that imports `web/rust-wgpu.js` as a module and awaits its default export, which
+//! runs `main`. The window's canvas is appended to the page's body.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::JsValue;
+
+/// forwards `log` records to the browser's console
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = JsValue::from(format!("[{}] {}", record.target(), record.args()));
+        match record.level() {
+            Level::Error => web_sys::console::error_1(&message),
+            Level::Warn => web_sys::console::warn_1(&message),
+            Level::Info => web_sys::console::info_1(&message),
+            Level::Debug | Level::Trace => web_sys::console::debug_1(&message),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// panics would otherwise only show up as an `unreachable` trap without a message
+pub fn init_logging() {
+    std::panic::set_hook(Box::new(|info| {
+        web_sys::console::error_1(&JsValue::from(info.to_string()));
+    }));
+    log::set_logger(&ConsoleLogger).expect("a logger is already set");
+    log::set_max_level(LevelFilter::Info);
+}